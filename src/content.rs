@@ -1,7 +1,27 @@
+use std::cmp;
+use std::rc::Rc;
+
+use document::{Block, Document, Inline};
+use emphasis::{process_emphasis, Delimiter};
+use entities;
+use fence::Fence;
+use html::{self, HtmlBlock, Unclosed};
+use link::{Bracket, InlineLink, Link, References};
+use list::ListMarker;
+use options::Options;
+use render::{HtmlRenderer, Renderer};
 use section::Section;
 use symbol::Symbol;
 use paragraph::Paragraph;
 
+/// The deepest that blockquotes and lists can be nested. Past it their
+/// markers are left as text, as the blocks are processed recursively.
+const MAX_DEPTH: usize = 100;
+
+/// The deepest that emphasis, links and images can be nested. Past it their
+/// delimiters are left as text, as the inlines are rendered recursively.
+const MAX_INLINE_DEPTH: usize = 100;
+
 #[derive(Clone, Copy, PartialEq)]
 #[allow(dead_code)]
 pub enum LineType {
@@ -10,285 +30,582 @@ pub enum LineType {
     Header,
     HorizontalRule,
     Blockquote,
+    List,
+    Item,
     Code,
+    FencedCode,
+    Html,
+
+    /// The document itself.
     Null,
 }
 
 #[allow(dead_code)]
 pub struct Content {
-    pub string: String,
+    pub chars: Vec<char>,
     pub symbols: Vec<Symbol>,
+    pub options: Options,
+    pub references: Rc<References>,
 }
 
 #[allow(dead_code)]
 impl Content {
 
-    /// INITIALIZATION //
+    // INITIALIZATION //
 
     /// Create a new input.
     pub fn from_str(input: &str) -> Content {
-        let string = input.to_string();
-        let symbols = Symbol::from_str(input);
-        return Content { symbols: symbols, string: string };
+        return Content::with_options(input, &Options::new());
+    }
+
+    /// Create a new input that is parsed with the given options.
+    pub fn with_options(input: &str, options: &Options) -> Content {
+        let string = Content::normalize_line_endings(input);
+        let chars = string.chars().collect();
+        let symbols = Symbol::from_str(&string);
+        let options = options.clone();
+        let references = Rc::new(References::new());
+        return Content {
+            symbols: symbols,
+            chars: chars,
+            options: options,
+            references: references,
+        };
+    }
+
+    /// Create an input nested inside of another one, which shares its
+    /// options and link references.
+    pub fn inherit(input: &str, parent: &Content) -> Content {
+        let mut content = Content::with_options(input, &parent.options);
+        content.references = parent.references.clone();
+        return content;
     }
 
     // PUBLIC FUNCTIONS //
 
-    pub fn convert(&self) -> String {
-        let mut output = String::new();
-        for paragraph in self.sections().iter() {
-            match paragraph.kind {
-                LineType::Blockquote => {
-                    output.push_str("<blockquote>");
-                    for i in Section::to_index(&paragraph.lines) {
-                        match self.string.chars().nth(i) {
-                            None => continue,
-                            Some(s) => output.push(s),
-                        }
-                    }
-                    output.push_str("</blockquote>");
-                },
-                LineType::Code => {
-                    output.push_str("<pre><code>");
-                    for i in Section::to_index(&paragraph.lines) {
-                        match self.string.chars().nth(i) {
-                            None => continue,
-                            Some(s) => output.push(s),
-                        }
-                    }
-                    output.push_str("</code></pre>");
-                },
-                LineType::Paragraph => {
-                    let mut line_slice: &[Section] = &paragraph.lines[..];
-
-                    // Check for fancy headers.
-                    if paragraph.lines.len() > 1 {
-                        let start_index = paragraph.lines[1].start;
-
-                        // 'Foo\n=='
-                        if self.symbols[start_index] == Symbol::EqualsSign {
-                            let seq_len = self.sequence_length(Symbol::EqualsSign, start_index);
-                            if seq_len > 1 && seq_len == paragraph.lines[1].end - paragraph.lines[1].start {
-                                output.push_str("<h1>");
-                                output.push_str(&self.string[paragraph.lines[0].start..paragraph.lines[0].end]);
-                                output.push_str("</h1>");
-                                if paragraph.lines.len() > 2 {
-                                    line_slice = &paragraph.lines[2..paragraph.lines.len()];
-                                } else {
-                                    continue;
-                                }
-                            }
+    pub fn convert(&mut self) -> String {
+        let mut renderer = HtmlRenderer::with_options(self.options.clone());
+        return renderer.render(&self.document());
+    }
 
-                        // 'Foo\n--'
-                        } else if self.symbols[start_index] == Symbol::Hyphen {
-                            let seq_len = self.sequence_length(Symbol::Hyphen, start_index);
-                            if seq_len > 1 && seq_len == paragraph.lines[1].end - paragraph.lines[1].start {
-                                output.push_str("<h2>");
-                                output.push_str(&self.string[paragraph.lines[0].start..paragraph.lines[0].end]);
-                                output.push_str("</h2>");
-                                if paragraph.lines.len() > 2 {
-                                    line_slice = &paragraph.lines[2..paragraph.lines.len()];
-                                } else {
-                                    continue;
-                                }
-                            }
-                        }
-                    }
-                    output.push_str(&self.convert_paragraph(line_slice));
+    /// Build the document tree.
+    pub fn document(&mut self) -> Document {
+        let sections = self.sections();
+        self.add_references(&sections);
+        return Document::new(self.blocks(&sections));
+    }
+
+    /// Collect the link reference definitions found anywhere in the sections
+    /// so that links can use them.
+    pub fn add_references(&mut self, sections: &[Paragraph]) {
+        let mut references = References::new();
+        self.find_references(sections, &mut references);
+        self.references = Rc::new(references);
+    }
+
+    fn blocks(&self, sections: &[Paragraph]) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for paragraph in sections.iter() {
+            blocks.append(&mut self.convert_section(paragraph));
+        }
+        return blocks;
+    }
+
+    /// Build the blocks found in a single section.
+    pub fn convert_section(&self, paragraph: &Paragraph) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        match paragraph.kind {
+            LineType::Blockquote => {
+                blocks.push(Block::BlockQuote(self.blocks(&paragraph.children)));
+            },
+            LineType::Code => {
+                let literal = self.lines_to_string(&paragraph.lines);
+                blocks.push(Block::CodeBlock { info: String::new(), literal: literal });
+            },
+            LineType::FencedCode => {
+                let info = paragraph.fence.as_ref().map_or(String::new(), |fence| fence.info.clone());
+                let literal = self.lines_to_string(&paragraph.lines);
+                blocks.push(Block::CodeBlock { info: info, literal: literal });
+            },
+            LineType::Html => {
+                let literal = self.lines_to_string(&paragraph.lines);
+                blocks.push(Block::HtmlBlock(literal.trim_end_matches('\n').to_string()));
+            },
+            LineType::List => {
+                blocks.push(self.convert_list(paragraph));
+            },
+            LineType::Paragraph => {
+
+                // Link reference definitions are not shown.
+                let (_, definition_count) = self.definitions(&paragraph.lines);
+                let line_slice: &[Section] = &paragraph.lines[definition_count..];
+                if line_slice.is_empty() {
+                    return blocks;
                 }
-                LineType::Header => {
-                    if paragraph.lines.len() > 1 {
-                        panic!("Header contains more than one line.")
-                    }
-                    output.push_str(&self.convert_header(&paragraph.lines[0]));
-                },
+                blocks.push(self.convert_paragraph(line_slice));
+            }
 
-                LineType::HorizontalRule => {
-                    output.push_str("<hr />")
+            // '# Foo'
+            LineType::Header if paragraph.lines.len() == 1 => {
+                blocks.push(self.convert_header(&paragraph.lines[0]));
+            },
+
+            // 'Foo\nbar\n==='
+            LineType::Header => {
+                let (underline, lines) = paragraph.lines.split_last().expect("Missing setext underline.");
+                let (_, definition_count) = self.definitions(lines);
+                let line_slice: &[Section] = &lines[definition_count..];
+                let level = match self.symbols[underline.start] {
+                    Symbol::EqualsSign => 1,
+                    _ => 2,
+                };
+
+                // When the whole paragraph is definitions the underline is
+                // all that is left.
+                if line_slice.is_empty() {
+                    blocks.push(match level {
+                        1 => self.convert_paragraph(&paragraph.lines[paragraph.lines.len() - 1..]),
+                        _ => Block::ThematicBreak,
+                    });
+                    return blocks;
                 }
+                let text = self.lines_to_string(line_slice);
+                let content = self.parse_inline(text.trim_end_matches([' ', '\t', '\n']));
+                blocks.push(Block::Heading { level: level, content: content });
+            },
 
-                // This should not happen.
-                _ => continue,
+            LineType::HorizontalRule => {
+                blocks.push(Block::ThematicBreak);
             }
+
+            // This should not happen.
+            _ => {},
         }
-        return output;
+        return blocks;
     }
 
     // CONVERSION FUNCTIONS //
 
-    fn parse_section(&self, indexes: &[usize]) -> String {
-        let mut output = String::new();
-        if indexes.len() < 1 {
-            return output;
-        }
-        let mut next = indexes[0];
-        for index in indexes {
-            let i = *index;
-            if i < next {
-                continue;
-            } else {
-                next += 1;
-            }
-            match self.symbols[i] {
+    /// Parse the inline content between `start` and `end`.
+    fn parse_section(&self, start: usize, end: usize) -> Vec<Inline> {
+        let mut inlines: Vec<Inline> = Vec::new();
+        let mut delimiters: Vec<Delimiter> = Vec::new();
+        let mut brackets: Vec<Bracket> = Vec::new();
 
-                // Emphasis characters.
-                Symbol::Asterisk => {
-                    let search_symbols: &[Symbol];
-                    let length = self.sequence_length(Symbol::Asterisk, i);
-                    if length > 3 {
-                        search_symbols = &self.symbols[i..i + 3];
-                    } else {
-                        search_symbols = &self.symbols[i..i + length];
-                    }
+        // The depth of each link and image, by its position in the inlines.
+        let mut nested: Vec<(usize, usize)> = Vec::new();
+        let mut unclosed = Unclosed::default();
+        let mut text = String::new();
+        let mut i = start;
+        while i < end {
+            match self.symbols[i] {
 
-                    // Emphasis characters need to be attached to something.
-                    // For example `*foo*` is valid; however, `* foo *` is not.
-                    if self.symbols[i + length].is_blank() {
-                        output.push('*');
-                    } else {
-                        match self.find_next_slice(search_symbols, i + length) {
-                            None => {
-                                let mut index = 0;
-                                while index < length {
-                                    output.push('*');
-                                    index += 1;
-                                }
-                                next = i + length;
-                            },
-                            Some(int) => {
-                                if length > 3 {
-                                    let mut index = 0;
-                                    while index < length - 3 {
-                                        output.push('*');
-                                        index += 1;
-                                    }
-                                }
-                                if self.symbols[int - 1].is_blank() {
-                                    output.push('*')
-                                } else {
-                                    match length {
-                                        1 => output.push_str("<em>"),
-                                        2 => output.push_str("<strong>"),
-                                        _ => output.push_str("<strong><em>"),
-                                    }
-
-                                    // TODO: Check this section for code blocks.
-                                    let subsection = &indexes[i + length..int];
-                                    output.push_str(&self.parse_section(subsection));
-                                    match length {
-                                        1 => output.push_str("</em>"),
-                                        2 => output.push_str("</strong>"),
-                                        _ => output.push_str("</em></strong>"),
-                                    }
-                                    next = int + search_symbols.len();
-                                }
-                            },
-                        }
-                    }
+                // Emphasis characters. Whether a run opens or closes emphasis
+                // depends on what is around it, so they are matched up once
+                // everything else has been parsed.
+                Symbol::Asterisk | Symbol::Underscore => {
+                    let length = cmp::min(self.sequence_length(Symbol::from_char(self.chars[i]), i), end - i);
+                    let before = if i == 0 { ' ' } else { self.chars[i - 1] };
+                    let after = self.chars.get(i + length).cloned().unwrap_or(' ');
+                    Content::flush_text(&mut inlines, &mut text);
+                    delimiters.push(Delimiter::from_run(inlines.len(), self.chars[i], length, before, after));
+                    Content::push_repeated(&mut text, self.chars[i], length);
+                    Content::flush_text(&mut inlines, &mut text);
+                    i += length;
                 },
 
                 // Inlines code such as '`code`' can only be broken by an equal number of
                 // backtick characters.
                 Symbol::Code => {
-                    let length = self.sequence_length(Symbol::Code, i);
-                    let search_symbols = &self.symbols[i..i + length];
-                    match self.find_next_slice(search_symbols, i + length) {
+                    let length = cmp::min(self.sequence_length(Symbol::Code, i), end - i);
+                    match self.find_code_span_end(length, i + length, end) {
                         None => {
-                            let mut index = 0;
-                            while index < length {
-                                output.push('`');
-                                index += 1;
-                            }
-                            next = i + length;
+                            Content::push_repeated(&mut text, '`', length);
+                            i += length;
                         },
                         Some(int) => {
-                            output.push_str("<code>");
-                            output.push_str(&self.string[i + length..int]);
-                            output.push_str("</code>");
-                            next = int + search_symbols.len();
+                            Content::flush_text(&mut inlines, &mut text);
+                            inlines.push(Inline::Code(Content::code_span(&self.slice(i + length, int))));
+                            i = int + length;
                         },
                     }
                 },
-                _ => {
-                    match self.string.chars().nth(i) {
-                        None => continue,
-                        Some(s) => output.push(s),
+                // Links such as '[text](href "title")' and images such as
+                // '![alt](src "title")'. The text is parsed like everything
+                // else, and only becomes a link once the closing bracket and
+                // the destination after it are found.
+                Symbol::LeftBracket => {
+                    Content::flush_text(&mut inlines, &mut text);
+                    brackets.push(Bracket::new(inlines.len(), delimiters.len(), i + 1, false));
+                    inlines.push(Inline::Text("[".to_string()));
+                    i += 1;
+                },
+                Symbol::Other if self.chars[i] == '!' && i + 1 < end && self.symbols[i + 1] == Symbol::LeftBracket => {
+                    Content::flush_text(&mut inlines, &mut text);
+                    brackets.push(Bracket::new(inlines.len(), delimiters.len(), i + 2, true));
+                    inlines.push(Inline::Text("![".to_string()));
+                    i += 2;
+                },
+                Symbol::RightBracket => {
+                    let bracket = brackets.pop();
+
+                    // Links and images around inlines that are already nested
+                    // as deep as they can be are left as text.
+                    let link = match bracket {
+                        Some(ref bracket) if bracket.active && bracket.depth < MAX_INLINE_DEPTH => {
+                            InlineLink::from_text(self, Section::new(bracket.start, i), end)
+                        },
+                        _ => None,
+                    };
+                    let (bracket, link) = match (bracket, link) {
+                        (Some(bracket), Some(link)) => (bracket, link),
+                        (bracket, _) => {
+                            if let (Some(bracket), Some(outer)) = (bracket, brackets.last_mut()) {
+                                outer.depth = cmp::max(outer.depth, bracket.depth);
+                            }
+                            text.push(']');
+                            i += 1;
+                            continue;
+                        },
+                    };
+
+                    // The inlines after the opening bracket become the text,
+                    // with emphasis matched inside of it.
+                    Content::flush_text(&mut inlines, &mut text);
+                    let mut children: Vec<Inline> = inlines.drain(bracket.index + 1..).collect();
+                    inlines.pop();
+                    let mut inner_delimiters = delimiters.split_off(bracket.delimiters);
+                    for inner in inner_delimiters.iter_mut() {
+                        inner.index -= bracket.index + 1;
+                    }
+                    let split = nested.partition_point(|&(index, _)| index <= bracket.index);
+                    let inner_nested: Vec<(usize, usize)> = nested.split_off(split).into_iter()
+                        .map(|(index, depth)| (index - bracket.index - 1, depth))
+                        .collect();
+                    let depth = process_emphasis(&mut children, &mut inner_delimiters, &inner_nested, MAX_INLINE_DEPTH - 1);
+                    Content::add_nested(&mut nested, &mut brackets, inlines.len(), depth + 1);
+                    if bracket.image {
+                        inlines.push(Inline::Image {
+                            destination: link.metadata.href,
+                            title: link.metadata.title,
+                            content: children,
+                        });
+                    } else {
+                        for bracket in brackets.iter_mut().filter(|bracket| !bracket.image) {
+                            bracket.active = false;
+                        }
+                        inlines.push(Inline::Link {
+                            destination: link.metadata.href,
+                            title: link.metadata.title,
+                            content: children,
+                        });
+                    }
+                    i = link.end;
+                },
+
+                // Autolinks such as '<http://example.com>', and raw HTML.
+                Symbol::LessThan => {
+                    if let Some(link) = InlineLink::from_autolink(self, &Section::new(i, end)) {
+                        Content::flush_text(&mut inlines, &mut text);
+                        let link_text = self.slice(link.text.start, link.text.end);
+                        Content::add_nested(&mut nested, &mut brackets, inlines.len(), 1);
+                        inlines.push(Inline::Link {
+                            destination: link.metadata.href,
+                            title: None,
+                            content: vec![Inline::Text(link_text)],
+                        });
+                        i = link.end;
+                    } else if let Some(length) = html::inline_length(&self.chars[i..end], &mut unclosed) {
+                        Content::flush_text(&mut inlines, &mut text);
+                        inlines.push(Inline::Html(self.slice(i, i + length)));
+                        i += length;
+                    } else {
+                        text.push('<');
+                        i += 1;
                     }
+                },
+
+                // A backslash escapes ASCII punctuation, and at the end of a
+                // line it forces a line break.
+                Symbol::Escape => {
+                    match self.chars.get(i + 1) {
+                        Some(&c) if i + 1 < end && c.is_ascii_punctuation() => {
+                            text.push(c);
+                            i += 2;
+                        },
+                        Some(_) if i + 1 < end && self.symbols[i + 1] == Symbol::Newline => {
+                            Content::flush_text(&mut inlines, &mut text);
+                            inlines.push(Inline::HardBreak);
+                            i += 2;
+                        },
+                        _ => {
+                            text.push('\\');
+                            i += 1;
+                        },
+                    }
+                },
+
+                // Entity and numeric character references such as '&copy;'.
+                Symbol::Ampersand => {
+                    match entities::from_chars(&self.chars[i..end]) {
+                        Some((decoded, length)) => {
+                            text.push_str(&decoded);
+                            i += length;
+                        },
+                        None => {
+                            text.push('&');
+                            i += 1;
+                        },
+                    }
+                },
+
+                // Spaces and tabs around a line ending are not part of the
+                // text, but two or more spaces at the end of a line force a
+                // line break.
+                Symbol::Newline => {
+                    let mut spaces = 0;
+                    while spaces < i && self.symbols[i - spaces - 1] == Symbol::Space {
+                        spaces += 1;
+                    }
+                    let length = text.trim_end_matches([' ', '\t']).len();
+                    text.truncate(length);
+                    Content::flush_text(&mut inlines, &mut text);
+                    if spaces >= 2 {
+                        inlines.push(Inline::HardBreak);
+                    } else {
+                        inlines.push(Inline::SoftBreak);
+                    }
+                    i += 1;
+                    while i < end && self.symbols[i].is_blank() {
+                        i += 1;
+                    }
+                },
+                _ => {
+                    text.push(self.chars[i]);
+                    i += 1;
                 }
             }
         }
-        return output;
+        Content::flush_text(&mut inlines, &mut text);
+        process_emphasis(&mut inlines, &mut delimiters, &nested, MAX_INLINE_DEPTH);
+        return inlines;
     }
 
-    fn convert_paragraph(&self, lines: &[Section]) -> String {
-        let mut output = String::new();
-        output.push_str("<p>");
-        output.push_str(&self.parse_section(&Section::slice_to_index(lines)[..]));
-        output.push_str("</p>");
-        return output;
+    /// Parse all of the content as inlines.
+    fn inlines(&self) -> Vec<Inline> {
+        return self.parse_section(0, self.len());
     }
 
-    fn convert_header(&self, line: &Section) -> String {
-        let mut output = String::new();
+    fn convert_paragraph(&self, lines: &[Section]) -> Block {
+        // The final line ending is not part of the paragraph, and neither
+        // are spaces and tabs before it.
+        let text = self.lines_to_string(lines);
+        return Block::Paragraph(self.parse_inline(text.trim_end_matches([' ', '\t', '\n'])));
+    }
+
+    fn convert_header(&self, line: &Section) -> Block {
         let depth = self.sequence_length(Symbol::NumberSign, line.start);
-        let opening_tag = match depth {
-            5 => "<h5>",
-            4 => "<h4>",
-            3 => "<h3>",
-            2 => "<h2>",
-            1 => "<h1>",
-            _ => "<h6>",
-        };
-        output.push_str(opening_tag);
         let subsection = self.strip_paragraph_line(line.start + depth, line.end);
+        let mut end = self.trim_blank_end(subsection.start, subsection.end);
 
-        // TODO: Parse inside headers.
-        output.push_str(&self.string[subsection.start..subsection.end]);
-        let closing_tag = match depth {
-            5 => "</h5>",
-            4 => "</h4>",
-            3 => "</h3>",
-            2 => "</h2>",
-            1 => "</h1>",
-            _ => "</h6>",
-        };
-        output.push_str(closing_tag);
-        return output;
+        // The closing sequence in '# Foo ##' is optional, and has to be
+        // separated from the text by a space.
+        let mut hashes = end;
+        while hashes > subsection.start && self.symbols[hashes - 1] == Symbol::NumberSign {
+            hashes -= 1;
+        }
+        if hashes == subsection.start || self.symbols[hashes - 1].is_blank() {
+            end = self.trim_blank_end(subsection.start, hashes);
+        }
+        let text = self.slice(subsection.start, end);
+        return Block::Heading { level: cmp::min(depth, 6), content: self.parse_inline(&text) };
+    }
+
+    /// Parse text, such as the lines of a paragraph, as inline content.
+    fn parse_inline(&self, text: &str) -> Vec<Inline> {
+        return Content::inherit(text, self).inlines();
+    }
+
+    fn convert_list(&self, list: &Paragraph) -> Block {
+        let mut items: Vec<Vec<Block>> = Vec::new();
+        for item in list.children.iter() {
+            items.push(self.blocks(&item.children));
+        }
+        let start = list.marker.and_then(|marker| marker.start);
+        return Block::List { start: start, tight: list.tight, items: items };
+    }
+
+    /// Find the link reference definitions at the start of a paragraph,
+    /// along with the number of lines they take up.
+    fn definitions(&self, lines: &[Section]) -> (Vec<Link>, usize) {
+        let inner = Content::inherit(&self.lines_to_string(lines), self);
+        let mut links: Vec<Link> = Vec::new();
+        let mut index = 0;
+        while index < inner.len() {
+            match Link::from_definition(&inner, index) {
+                None => break,
+                Some((link, next)) => {
+                    links.push(link);
+                    index = next;
+                },
+            }
+        }
+        let mut line_count = inner.chars[..index].iter().filter(|&&c| c == '\n').count();
+        if index == inner.len() && index > 0 && inner.chars[index - 1] != '\n' {
+            line_count += 1;
+        }
+        return (links, line_count);
+    }
+
+    fn find_references(&self, sections: &[Paragraph], references: &mut References) {
+        for paragraph in sections.iter() {
+            match paragraph.kind {
+                LineType::Paragraph => {
+
+                    // The first definition of a label is the one that counts.
+                    for link in self.definitions(&paragraph.lines).0 {
+                        references.entry(link.id).or_insert(link.metadata);
+                    }
+                },
+
+                // Definitions can come before the text of a setext heading.
+                LineType::Header if paragraph.lines.len() > 1 => {
+                    let lines = &paragraph.lines[..paragraph.lines.len() - 1];
+                    for link in self.definitions(lines).0 {
+                        references.entry(link.id).or_insert(link.metadata);
+                    }
+                },
+                LineType::Blockquote | LineType::List | LineType::Item => {
+                    self.find_references(&paragraph.children, references);
+                },
+                _ => continue,
+            }
+        }
     }
 
     // HELPER FUNCTIONS //
 
+    /// Turn '\r\n' and '\r' line endings into '\n', so that every line
+    /// ends with a single character.
+    fn normalize_line_endings(input: &str) -> String {
+        if !input.contains('\r') {
+            return input.to_string();
+        }
+        return input.replace("\r\n", "\n").replace('\r', "\n");
+    }
+
+    /// The number of characters in the content.
+    pub fn len(&self) -> usize {
+        return self.chars.len();
+    }
+
+    /// Copy the characters between `start` and `end`.
+    pub fn slice(&self, start: usize, end: usize) -> String {
+        return self.chars[start..end].iter().cloned().collect();
+    }
+
+    /// Copy the characters of each line, including the line ending.
+    fn lines_to_string(&self, lines: &[Section]) -> String {
+        let mut output = String::new();
+        for line in lines.iter() {
+            Content::push_repeated(&mut output, ' ', line.padding);
+            for i in line.start..line.end + 1 {
+                if let Some(c) = self.chars.get(i) {
+                    output.push(*c);
+                }
+            }
+        }
+        return output;
+    }
+
+    /// Line endings in a code span become spaces, and a single space is
+    /// stripped from each end so that code can start or end with a backtick.
+    fn code_span(code: &str) -> String {
+        let code = code.replace('\n', " ");
+        if code.len() > 1 && code.starts_with(' ') && code.ends_with(' ') && !code.chars().all(|c| c == ' ') {
+            return code[1..code.len() - 1].to_string();
+        }
+        return code;
+    }
+
+    fn flush_text(inlines: &mut Vec<Inline>, text: &mut String) {
+        if !text.is_empty() {
+            inlines.push(Inline::Text(text.clone()));
+            text.clear();
+        }
+    }
+
+    /// Record the depth of a link or image that is about to be added to the
+    /// inlines at `index`.
+    fn add_nested(nested: &mut Vec<(usize, usize)>, brackets: &mut [Bracket], index: usize, depth: usize) {
+        nested.push((index, depth));
+        if let Some(bracket) = brackets.last_mut() {
+            bracket.depth = cmp::max(bracket.depth, depth);
+        }
+    }
+
+    fn push_repeated(text: &mut String, character: char, count: usize) {
+        for _ in 0..count {
+            text.push(character);
+        }
+    }
+
     /// Check if the inputted string slice is found at the given index.
     fn check_match(&self, key: &Symbol, index: usize) -> bool {
-        if index > self.string.len() {
+        if index > self.len() {
             return false;
         }
         return &self.symbols[index] == key;
     }
 
     fn find_next(&self, symbol: Symbol, index: usize) -> Option<usize> {
-        if index > self.string.len() {
+        if index > self.len() {
             return None;
         }
-        for i in index..self.string.len() {
-            if self.symbols[i] == symbol {
+        return (index..self.len()).find(|&i| self.symbols[i] == symbol);
+    }
+
+    /// Find the next run of exactly `length` backticks before `end`.
+    fn find_code_span_end(&self, length: usize, index: usize, end: usize) -> Option<usize> {
+        let mut i = index;
+        while i < end {
+            if self.symbols[i] != Symbol::Code {
+                i += 1;
+                continue;
+            }
+            let run = cmp::min(self.sequence_length(Symbol::Code, i), end - i);
+            if run == length {
                 return Some(i);
             }
+            i += run;
         }
         return None;
     }
 
-    fn find_next_slice(&self, symbol: &[Symbol], index: usize) -> Option<usize> {
-        for i in index + symbol.len()..self.string.len() {
+    /// Find the next place the symbols are repeated before `end`.
+    pub fn find_next_slice(&self, symbol: &[Symbol], index: usize, end: usize) -> Option<usize> {
+        let mut i = index;
+        while i + symbol.len() <= end {
             if &self.symbols[i..i + symbol.len()] == symbol {
                 return Some(i);
             }
+            i += 1;
         }
         return None;
     }
 
+    /// Check if the character at the index follows an odd number of
+    /// backslashes.
+    pub fn is_escaped(&self, index: usize) -> bool {
+        let mut count = 0;
+        while count < index && self.symbols[index - count - 1] == Symbol::Escape {
+            count += 1;
+        }
+        return count % 2 == 1;
+    }
+
     /// Check how many times (if any) a character is repeated.
-    fn sequence_length(&self, key: Symbol, index: usize) -> usize {
+    pub fn sequence_length(&self, key: Symbol, index: usize) -> usize {
         let mut length: usize = 0;
         loop {
             if self.check_match(&key, index + length) {
@@ -308,6 +625,41 @@ impl Content {
         return true;
     }
 
+    /// Count the spaces and tabs starting at `start`, which is found at the
+    /// given column of its line. Returns both the number of characters and
+    /// the number of columns they take up.
+    pub fn spacing(&self, start: usize, end: usize, column: usize) -> (usize, usize) {
+        let tab_width = cmp::max(self.options.tab_width, 1);
+        let mut columns = column;
+        let mut index = start;
+        while index < end {
+            match self.symbols[index] {
+                Symbol::Space => columns += 1,
+                Symbol::Tab => columns += tab_width - columns % tab_width,
+                _ => break,
+            }
+            index += 1;
+        }
+        return (index - start, columns - column);
+    }
+
+    /// Check for a line of '=' or '-', which turns the paragraph above it
+    /// into a heading.
+    fn is_setext_underline(&self, start: usize, end: usize, column: usize) -> bool {
+        let (skip, columns) = self.spacing(start, end, column);
+        let first = start + skip;
+        if columns > 3 || first == end {
+            return false;
+        }
+        let symbol = match self.symbols[first] {
+            Symbol::EqualsSign => Symbol::EqualsSign,
+            Symbol::Hyphen => Symbol::Hyphen,
+            _ => return false,
+        };
+        let length = self.sequence_length(symbol, first);
+        return self.is_blank(first + length, end);
+    }
+
     fn is_horizonal_rule(&self, start: usize, end: usize) -> bool {
         let search_symbol = match self.symbols[start] {
             Symbol::Asterisk => Symbol::Asterisk,
@@ -327,23 +679,15 @@ impl Content {
             }
             index += 1;
         }
-        if symbol_count >= 3 {
-            return true;
-        } else {
-            return false;
-        }
+        return symbol_count >= 3;
     }
 
-    fn find_line_type(&self, start: usize, end: usize) -> LineType {
+    fn find_line_type(&self, start: usize, end: usize, column: usize) -> LineType {
         if self.is_blank(start, end) {
             return LineType::Blank;
         }
-        let offset = match self.symbols[start] {
-            Symbol::Space => self.sequence_length(Symbol::Space, start),
-            Symbol::Tab => return LineType::Code,
-            _ => 0,
-        };
-        if offset > 3 {
+        let (offset, columns) = self.spacing(start, end, column);
+        if columns > 3 {
             return LineType::Code;
         }
         if self.is_horizonal_rule(start + offset, end) {
@@ -352,232 +696,382 @@ impl Content {
         match self.symbols[start + offset] {
             Symbol::NumberSign => {
                 // Number signs used in headers must be followed by a space
-                // and must not be longer than six characters long.
+                // or the end of the line and must not be longer than six
+                // characters long.
                 let number_of_hashes = self.sequence_length(Symbol::NumberSign, start + offset);
-                if self.symbols[start + offset + number_of_hashes] == Symbol::Space
-                    && number_of_hashes <= 6 {
+                let after = start + offset + number_of_hashes;
+                if (after == end || self.symbols[after].is_blank()) && number_of_hashes <= 6 {
                         return LineType::Header;
                     } else {
                         return LineType::Paragraph;
                     }
             },
             Symbol::GreaterThan => return LineType::Blockquote,
-            Symbol::Tab => return LineType::Code,
+            Symbol::LessThan if HtmlBlock::from_line(self, start, end, column).is_some() => return LineType::Html,
+            Symbol::Code | Symbol::Tilde if Fence::from_line(self, start, end, column).is_some() => {
+                return LineType::FencedCode;
+            },
+            Symbol::Asterisk | Symbol::Hyphen | Symbol::Plus
+                if ListMarker::from_line(self, start, end, column).is_some() => return LineType::List,
+
+            // Ordered list items such as '1. ' or '2) '.
+            Symbol::Alphanumeric if ListMarker::from_line(self, start, end, column).is_some() => {
+                return LineType::List;
+            },
             _ => return LineType::Paragraph,
         }
     }
 
-    fn sections(&self) -> Vec<Paragraph> {
-         let mut paragraphs: Vec<Paragraph> = Vec::new();
-         let mut lines: Vec<Section> = Vec::new();
-         let mut last_line_type = LineType::Null;
-         let mut index: usize = 0;
-         loop {
-             let next_newline = match self.find_next(Symbol::Newline, index) {
-                 None => {
-                     if index < self.string.len() {
-                         let line_type = self.find_line_type(index, self.string.len());
-                         if line_type == LineType::Blank {
-                             break;
-                         }
-                         let stripped_line = self.strip_line(line_type, index, self.string.len());
-                         if line_type == last_line_type {
-                             lines.push(stripped_line);
-                             paragraphs.push(Paragraph::new(lines, last_line_type));
-                             lines = Vec::new();
-                         } else {
-                             let is_header = last_line_type == LineType::Paragraph
-                                 && lines.len() == 1;
-                             if line_type == LineType::HorizontalRule {
-                                 // TODO: Check if the line follows the form '---'
-                                 if is_header {
-                                     lines.push(stripped_line);
-                                     paragraphs.push(Paragraph::new(lines, LineType::Paragraph));
-                                     lines = Vec::new();
-                                 } else {
-                                     paragraphs.push(Paragraph::new(lines, LineType::Paragraph));
-                                     lines = Vec::new();
-                                     lines.push(stripped_line);
-                                     paragraphs.push(Paragraph::new(lines, LineType::HorizontalRule));
-                                     lines = Vec::new();
-                                 }
-                                 break;
-                             }
-                             if lines.len() > 0 {
-                                 paragraphs.push(Paragraph::new(lines, last_line_type));
-                                 lines = Vec::new();
-                             }
-                             lines.push(stripped_line);
-                             paragraphs.push(Paragraph::new(lines, line_type));
-                             lines = Vec::new();
-                         }
-                     }
-                     break;
-                 },
-                 Some(int) => int,
-             };
-
-             // Skip extra newline characters at the start.
-             if next_newline - index == 0 {
-                 index += self.sequence_length(Symbol::Newline, index);
-                 continue;
-             }
-             let newline_count = self.sequence_length(Symbol::Newline, next_newline);
-             let line_type = self.find_line_type(index, next_newline);
-             let stripped_line = self.strip_line(line_type, index, next_newline);
-             if line_type == last_line_type {
-                 lines.push(Section::new(index, next_newline));
-                 index = next_newline + 1;
-             } else {
-                 let is_header = last_line_type == LineType::Paragraph
-                     && lines.len() == 1;
-                 if line_type == LineType::HorizontalRule {
-                     // TODO: Check if the line follows the form '---'
-                     if is_header {
-                         lines.push(stripped_line);
-                         index = next_newline + 1;
-                         continue;
-                     } else {
-                         if lines.len() > 0 {
-                             paragraphs.push(Paragraph::new(lines, last_line_type));
-                         }
-                         lines = Vec::new();
-                         lines.push(stripped_line);
-                         paragraphs.push(Paragraph::new(lines, LineType::HorizontalRule));
-                         lines = Vec::new();
-                         index = next_newline + 1;
-                         continue;
-                     }
-                 }
-                 match last_line_type {
-
-                     // This is the first line.
-                     LineType::Null => {
-                         if line_type != LineType::Blank {
-                             last_line_type = line_type;
-                             lines.push(stripped_line);
-                         }
-                         index = next_newline + 1;
-                     },
-
-                     LineType::Blockquote => {
-                         match line_type {
-
-                             LineType::Blank => {
-                                 paragraphs.push(Paragraph::new(lines, LineType::Blockquote));
-                                 lines = Vec::new();
-                                 last_line_type = LineType::Null;
-                                 index = next_newline + 1;
-                             },
-
-                             // Headers break blockquotes.
-                             LineType::Header => {
-                                 paragraphs.push(Paragraph::new(lines, LineType::Blockquote));
-                                 lines = Vec::new();
-                                 lines.push(stripped_line);
-                                 paragraphs.push(Paragraph::new(lines, LineType::Header));
-                                 lines = Vec::new();
-                                 last_line_type = LineType::Null;
-                                 index = next_newline + 1;
-                                 continue;
-                             },
-
-                             // Paragraph and code lines continue a blockquote.
-                             _ => {
-                                 lines.push(stripped_line);
-                                 last_line_type = line_type;
-                                 index = next_newline + 1;
-                             }
-                         }
-                     },
-
-                     LineType::Paragraph => {
-                         match line_type {
-
-                             LineType::Blank => {
-                                 paragraphs.push(Paragraph::new(lines, LineType::Paragraph));
-                                 lines = Vec::new();
-                                 last_line_type = LineType::Null;
-                                 index = next_newline + 1;
-                             },
-
-                             // Headers break paragraphs.
-                             LineType::Header => {
-                                 paragraphs.push(Paragraph::new(lines, LineType::Paragraph));
-                                 lines = Vec::new();
-                                 lines.push(stripped_line);
-                                 paragraphs.push(Paragraph::new(lines, LineType::Header));
-                                 lines = Vec::new();
-                                 last_line_type = LineType::Null;
-                                 index = next_newline + 1;
-                                 continue;
-                             },
-
-                             //  Blockquotes break paragraphs.
-                             LineType::Blockquote => {
-                                 paragraphs.push(Paragraph::new(lines, LineType::Paragraph));
-                                 lines = Vec::new();
-                                 lines.push(stripped_line);
-                                 last_line_type = line_type;
-                                 index = next_newline + 1;
-                                 continue;
-                             },
-
-                             // Lines of code do not break paragraphs.
-                             _ => {
-                                 lines.push(stripped_line);
-                             },
-                         }
-                     }
-
-                     // Anything that is not a line of code will break a line of code.
-                     LineType::Code => {
-                         last_line_type = line_type;
-                         paragraphs.push(Paragraph::new(lines, LineType::Code));
-                         lines = Vec::new();
-                         lines.push(stripped_line);
-                         index = next_newline + 1;
-                         continue;
-                     },
-                     _ => {
-                         last_line_type = line_type;
-                         index = next_newline + 1;
-                     }
-                 }
-             }
-             if newline_count > 1 {
-                 paragraphs.push(Paragraph::new(lines, last_line_type));
-                 last_line_type = LineType::Null;
-                 lines = Vec::new();
-                 index = next_newline + newline_count;
-                 continue;
-             }
-
-         }
-
-         // Add the line and paragraph if that has not already been done.
-         if lines.len() > 0 {
-             paragraphs.push(Paragraph::new(lines, last_line_type));
-         }
-         return paragraphs;
-     }
-
-     fn strip_line(&self, kind: LineType, start: usize, end: usize) -> Section {
-         return match kind {
-             LineType::Paragraph => self.strip_paragraph_line(start, end),
-             _ => self.strip_paragraph_line(start, end),
-         };
-     }
-
-     /// Remove extra space in a line.
-     fn strip_paragraph_line(&self, start: usize, end: usize) -> Section {
-         let mut line_start = start;
-         for i in start..end {
-             if !self.symbols[i].is_blank() {
-                 line_start = i;
-                 break;
-             }
-         }
-         return Section::new(line_start, end);
-     }
+    // BLOCK STRUCTURE //
+
+    /// Split the input into blocks. Each line continues whichever open
+    /// blockquotes, list items and code blocks it can, then may start new
+    /// blocks, and what is left is added to the deepest one.
+    pub fn sections(&self) -> Vec<Paragraph> {
+        let mut root = Paragraph::new(Vec::new(), LineType::Null);
+        let mut index = 0;
+        while index < self.len() {
+            let end = self.find_next(Symbol::Newline, index).unwrap_or(self.len());
+            self.add_line(&mut root, index, end);
+            index = end + 1;
+        }
+        self.close(&mut root);
+        return root.children;
+    }
+
+    fn add_line(&self, root: &mut Paragraph, start: usize, end: usize) {
+        let mut offset = start;
+        let mut column = 0;
+        let (mut depth, fence_closed) = self.continue_blocks(root, &mut offset, &mut column, end);
+
+        // The closing fence is not part of the code.
+        if fence_closed {
+            self.close(root.descendant(depth));
+            return;
+        }
+        let mut all_closed = depth == root.open_depth();
+        let mut tip = root.tip();
+        let mut consumed = false;
+        let mut new_item = false;
+
+        // Look for the start of new blocks. Nothing can start inside of code
+        // or HTML.
+        while !matches!(root.descendant(depth).kind, LineType::Code | LineType::FencedCode | LineType::Html) {
+            let (skip, columns) = self.spacing(offset, end, column);
+            let first = offset + skip;
+
+            // 'Foo\n---' makes a heading rather than a rule, but only when
+            // the paragraph is not a lazy continuation.
+            if root.descendant(depth).kind == LineType::Paragraph && self.is_setext_underline(offset, end, column) {
+                let line = self.strip_paragraph_line(first, end);
+                let paragraph = root.descendant(depth);
+                paragraph.lines.push(line);
+                paragraph.kind = LineType::Header;
+                self.close(paragraph);
+                consumed = true;
+                break;
+            }
+            match self.find_line_type(offset, end, column) {
+                LineType::Blockquote | LineType::List if depth >= MAX_DEPTH => break,
+                LineType::Blockquote => {
+                    self.add_child(root, &mut depth, Paragraph::new(Vec::new(), LineType::Blockquote));
+                    (offset, column) = self.skip_blockquote_marker(first, end, column + columns);
+                },
+                LineType::Header => {
+                    let mut header = Paragraph::new(vec![self.strip_paragraph_line(first, end)], LineType::Header);
+                    header.open = false;
+                    self.add_child(root, &mut depth, header);
+                    consumed = true;
+                },
+                LineType::HorizontalRule => {
+                    let line = self.strip_paragraph_line(first, end);
+                    let mut rule = Paragraph::new(vec![line], LineType::HorizontalRule);
+                    rule.open = false;
+                    self.add_child(root, &mut depth, rule);
+                    consumed = true;
+                },
+                LineType::List => {
+                    let marker = match ListMarker::from_line(self, offset, end, column) {
+                        Some(marker) => marker,
+                        None => break,
+                    };
+                    let parent = root.descendant(depth);
+                    if parent.kind == LineType::Paragraph && !marker.interrupts_paragraph() {
+                        break;
+                    }
+
+                    // Items with a different marker start a new list.
+                    let continues = parent.kind == LineType::List
+                        && parent.marker.is_some_and(|m| marker.continues(&m));
+                    if !continues {
+                        let mut list = Paragraph::new(Vec::new(), LineType::List);
+                        list.marker = Some(marker);
+                        self.add_child(root, &mut depth, list);
+                    }
+                    let mut item = Paragraph::new(Vec::new(), LineType::Item);
+                    item.marker = Some(marker);
+                    self.add_child(root, &mut depth, item);
+                    offset = marker.content;
+                    column += marker.indent;
+                    new_item = true;
+                },
+
+                LineType::Html => {
+                    let html = match HtmlBlock::from_line(self, offset, end, column) {
+                        Some(html) => html,
+                        None => break,
+                    };
+                    if tip == LineType::Paragraph && !html.interrupts_paragraph() {
+                        break;
+                    }
+                    let mut block = Paragraph::new(Vec::new(), LineType::Html);
+                    block.html = Some(html);
+                    self.add_child(root, &mut depth, block);
+                },
+
+                LineType::FencedCode => {
+                    let mut code = Paragraph::new(Vec::new(), LineType::FencedCode);
+                    code.fence = Fence::from_line(self, offset, end, column);
+                    self.add_child(root, &mut depth, code);
+                    consumed = true;
+                },
+
+                // Indented code cannot interrupt a paragraph.
+                LineType::Code if tip != LineType::Paragraph => {
+                    self.add_child(root, &mut depth, Paragraph::new(Vec::new(), LineType::Code));
+                    (offset, column) = self.skip_indentation(offset, end, column, 4);
+                },
+                _ => break,
+            }
+            all_closed = true;
+            tip = root.descendant(depth).kind;
+            if consumed {
+                break;
+            }
+        }
+
+        let blank = !consumed && self.is_blank(offset, end);
+
+        // Lazy continuation lines only have to continue the paragraph.
+        if !all_closed && !blank && tip == LineType::Paragraph {
+            let line = self.strip_paragraph_line(offset, end);
+            root.descendant(root.open_depth()).lines.push(line);
+            return;
+        }
+        self.close_below(root, depth);
+
+        // Remember blank lines so that lists can tell if they are loose.
+        let container = root.descendant(depth);
+        if blank {
+            if let Some(child) = container.children.last_mut() {
+                child.last_line_blank = true;
+            }
+        }
+        let last_line_blank = blank && match container.kind {
+            LineType::Blockquote | LineType::FencedCode => false,
+            LineType::Item => !(new_item && container.children.is_empty()),
+            _ => true,
+        };
+        for level in 0..depth + 1 {
+            root.descendant(level).last_line_blank = last_line_blank;
+        }
+
+        if consumed {
+            return;
+        }
+        let container = root.descendant(depth);
+        match container.kind {
+            LineType::Paragraph => container.lines.push(self.strip_paragraph_line(offset, end)),
+            LineType::Code | LineType::FencedCode => container.lines.push(self.leaf_line(start, offset, column, end)),
+
+            // HTML is kept as it is, and the end condition can be on the
+            // same line as the start.
+            LineType::Html => {
+                container.lines.push(self.leaf_line(start, offset, column, end));
+                let line = self.slice(offset, end);
+                if container.html.is_some_and(|html| html.is_ended_by(&line)) {
+                    self.close(container);
+                }
+            },
+            _ if !blank => {
+                let line = self.strip_paragraph_line(offset, end);
+                self.add_child(root, &mut depth, Paragraph::new(vec![line], LineType::Paragraph));
+            },
+            _ => {},
+        }
+    }
+
+    /// Match the line against the open blocks, moving `offset` and its
+    /// `column` past their markers and indentation. Returns the depth of the
+    /// last block that was matched, and whether the line closed fenced code.
+    fn continue_blocks(&self, root: &Paragraph, offset: &mut usize, column: &mut usize, end: usize) -> (usize, bool) {
+        let mut depth = 0;
+        let mut paragraph = root;
+        while let Some(child) = paragraph.open_child() {
+            let (skip, columns) = self.spacing(*offset, end, *column);
+            let blank = *offset + skip == end;
+            match child.kind {
+                LineType::Blockquote => {
+                    if columns > 3 || self.symbols[*offset + skip] != Symbol::GreaterThan {
+                        break;
+                    }
+                    (*offset, *column) = self.skip_blockquote_marker(*offset + skip, end, *column + columns);
+                },
+                LineType::Item => {
+                    let indent = child.marker.map_or(0, |marker| marker.indent);
+
+                    // An item can only start with one blank line.
+                    if blank && !child.children.is_empty() {
+                        *offset += skip;
+                        *column += columns;
+                    } else if !blank && columns >= indent {
+                        (*offset, *column) = self.skip_indentation(*offset, end, *column, indent);
+                    } else {
+                        break;
+                    }
+                },
+                LineType::Code => {
+                    if columns >= 4 {
+                        (*offset, *column) = self.skip_indentation(*offset, end, *column, 4);
+                    } else if blank {
+                        *offset += skip;
+                        *column += columns;
+                    } else {
+                        break;
+                    }
+                },
+                LineType::FencedCode => {
+                    if let Some(ref fence) = child.fence {
+                        if fence.is_closed_by(self, *offset, end, *column) {
+                            return (depth + 1, true);
+                        }
+                        (*offset, *column) = self.skip_indentation(*offset, end, *column, fence.indent);
+                    }
+                },
+                LineType::Html => {
+                    if blank && child.html.is_some_and(|html| html.ends_at_blank_line()) {
+                        break;
+                    }
+                },
+                LineType::List => {},
+                LineType::Paragraph if !blank => {},
+                _ => break,
+            }
+            depth += 1;
+            paragraph = child;
+        }
+        return (depth, false);
+    }
+
+    /// Add a block at `depth`, closing any blocks that are in the way.
+    fn add_child(&self, root: &mut Paragraph, depth: &mut usize, child: Paragraph) {
+        self.close_below(root, *depth);
+        while !root.descendant(*depth).can_contain(child.kind) {
+            self.close(root.descendant(*depth));
+            *depth -= 1;
+        }
+        root.descendant(*depth).children.push(child);
+        *depth += 1;
+    }
+
+    /// Close everything inside of the open block at `depth`.
+    fn close_below(&self, root: &mut Paragraph, depth: usize) {
+        if let Some(child) = root.descendant(depth).children.last_mut() {
+            self.close(child);
+        }
+    }
+
+    fn close(&self, paragraph: &mut Paragraph) {
+        if !paragraph.open {
+            return;
+        }
+        if let Some(child) = paragraph.children.last_mut() {
+            self.close(child);
+        }
+        paragraph.open = false;
+        match paragraph.kind {
+
+            // Blank lines at the end of code are not part of it.
+            LineType::Code => {
+                while paragraph.lines.last().is_some_and(|line| self.is_blank(line.start, line.end)) {
+                    paragraph.lines.pop();
+                }
+            },
+            LineType::List => paragraph.tight = paragraph.is_tight(),
+            _ => {},
+        }
+    }
+
+    /// Skip a '>' found at `column`, and the space that may follow it.
+    fn skip_blockquote_marker(&self, index: usize, end: usize, column: usize) -> (usize, usize) {
+        return self.skip_indentation(index + 1, end, column + 1, 1);
+    }
+
+    /// Skip up to `columns` columns of indentation starting at `column`,
+    /// returning the index and column it stops at. When only part of a tab
+    /// is needed, it stops on the tab with the rest of it left over.
+    pub fn skip_indentation(&self, start: usize, end: usize, column: usize, columns: usize) -> (usize, usize) {
+        let tab_width = cmp::max(self.options.tab_width, 1);
+        let target = column + columns;
+        let mut column = column;
+        let mut index = start;
+        while index < end && column < target {
+            match self.symbols[index] {
+                Symbol::Space => column += 1,
+                Symbol::Tab => {
+                    let next = column + tab_width - column % tab_width;
+                    if next > target {
+                        return (index, target);
+                    }
+                    column = next;
+                },
+                _ => break,
+            }
+            index += 1;
+        }
+        return (index, column);
+    }
+
+    /// The rest of the line from `offset`, which is at `column`, for code
+    /// and HTML. What is left of a tab that was partly used as indentation
+    /// becomes spaces.
+    fn leaf_line(&self, start: usize, offset: usize, column: usize, end: usize) -> Section {
+        if offset == end || self.symbols[offset] != Symbol::Tab {
+            return Section::new(offset, end);
+        }
+        let tab_width = cmp::max(self.options.tab_width, 1);
+        let mut tab_column = 0;
+        for i in start..offset {
+            match self.symbols[i] {
+                Symbol::Tab => tab_column += tab_width - tab_column % tab_width,
+                _ => tab_column += 1,
+            }
+        }
+        if column == tab_column {
+            return Section::new(offset, end);
+        }
+        let tab_end = tab_column + tab_width - tab_column % tab_width;
+        return Section::padded(tab_end - column, offset + 1, end);
+    }
+
+    /// Remove extra space in a line.
+    /// Move `end` back past any spaces and tabs before it.
+    fn trim_blank_end(&self, start: usize, end: usize) -> usize {
+        let mut end = end;
+        while end > start && self.symbols[end - 1].is_blank() {
+            end -= 1;
+        }
+        return end;
+    }
+
+    fn strip_paragraph_line(&self, start: usize, end: usize) -> Section {
+        let mut line_start = start;
+        for i in start..end {
+            if !self.symbols[i].is_blank() {
+                line_start = i;
+                break;
+            }
+        }
+        return Section::new(line_start, end);
+    }
 }
@@ -0,0 +1,74 @@
+//! The document tree.
+//!
+//! A markdown document is made up of blocks (paragraphs, headings, code
+//! blocks and so on), some of which contain inline content (text, emphasis,
+//! code spans and links). Everything in the tree is owned, so a document can
+//! be inspected or rewritten freely before it is rendered.
+
+use content::Content;
+
+/// A parsed markdown document.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Document {
+    pub blocks: Vec<Block>,
+}
+
+/// Block level content.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Block {
+    /// An ATX (`# Foo`) or setext (`Foo\n===`) heading.
+    Heading { level: usize, content: Vec<Inline> },
+    Paragraph(Vec<Inline>),
+    /// Preformatted code. The info string of fenced code usually names its
+    /// language, and is empty for indented code. The literal is not parsed
+    /// any further.
+    CodeBlock { info: String, literal: String },
+    /// Raw HTML, which is passed through as it is.
+    HtmlBlock(String),
+    BlockQuote(Vec<Block>),
+    /// A bullet list, or an ordered list when it has a start number. The
+    /// paragraphs of a tight list are not wrapped in `<p>` tags.
+    List { start: Option<usize>, tight: bool, items: Vec<Vec<Block>> },
+    ThematicBreak,
+}
+
+/// Inline content found inside paragraphs and headings.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Inline {
+    Text(String),
+    Code(String),
+    Emphasis(Vec<Inline>),
+    Strong(Vec<Inline>),
+    Link { destination: String, title: Option<String>, content: Vec<Inline> },
+    /// An image. The content is its description, which is used as alt text.
+    Image { destination: String, title: Option<String>, content: Vec<Inline> },
+    /// Raw HTML, such as a tag or a comment.
+    Html(String),
+    /// A line ending inside a paragraph.
+    SoftBreak,
+    /// A forced line break.
+    HardBreak,
+}
+
+impl Document {
+    pub fn new(blocks: Vec<Block>) -> Document {
+        return Document { blocks: blocks };
+    }
+
+    /// Parse a string of markdown into a document.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use johnmark::{Block, Document, Inline};
+    ///
+    /// let document = Document::from_str("# Header");
+    /// assert_eq!(
+    ///     vec![Block::Heading { level: 1, content: vec![Inline::Text("Header".to_string())] }],
+    ///     document.blocks
+    /// );
+    /// ```
+    pub fn from_str(input: &str) -> Document {
+        return Content::from_str(input).document();
+    }
+}
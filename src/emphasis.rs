@@ -0,0 +1,250 @@
+//! Emphasis and strong emphasis.
+//!
+//! Runs of `*` and `_` are first added to the inlines as text, and are
+//! matched up once the rest of the inlines are known, following the
+//! CommonMark delimiter run rules.
+
+use std::cmp;
+
+use document::Inline;
+
+/// A run of `*` or `_` characters that may open or close emphasis.
+pub struct Delimiter {
+    /// The position of the run's text in the inlines.
+    pub index: usize,
+    pub character: char,
+    /// The number of characters that have not been used yet.
+    pub count: usize,
+    /// The number of characters in the whole run.
+    pub length: usize,
+    pub can_open: bool,
+    pub can_close: bool,
+    /// The number of characters used by each emphasis the run closes and
+    /// opens, innermost first.
+    closes: Vec<usize>,
+    opens: Vec<usize>,
+}
+
+impl Delimiter {
+    /// Describe a run of `length` characters between the characters
+    /// `before` and `after`. The start and end of the input count as
+    /// whitespace.
+    pub fn from_run(index: usize, character: char, length: usize, before: char, after: char) -> Delimiter {
+        let left_flanking = !after.is_whitespace()
+            && (!is_punctuation(after) || before.is_whitespace() || is_punctuation(before));
+        let right_flanking = !before.is_whitespace()
+            && (!is_punctuation(before) || after.is_whitespace() || is_punctuation(after));
+
+        // Underscores cannot open or close emphasis inside of a word.
+        let (can_open, can_close) = match character {
+            '_' => (
+                left_flanking && (!right_flanking || is_punctuation(before)),
+                right_flanking && (!left_flanking || is_punctuation(after)),
+            ),
+            _ => (left_flanking, right_flanking),
+        };
+        return Delimiter {
+            index: index,
+            character: character,
+            count: length,
+            length: length,
+            can_open: can_open,
+            can_close: can_close,
+            closes: Vec::new(),
+            opens: Vec::new(),
+        };
+    }
+
+    fn matches(&self, closer: &Delimiter) -> bool {
+        if self.character != closer.character || !self.can_open || self.count == 0 {
+            return false;
+        }
+
+        // The rule of 3: when either run can both open and close, their
+        // lengths cannot add up to a multiple of 3 unless both are.
+        let either_both = self.can_close || closer.can_open;
+        let multiple_of_3 = (self.length + closer.length) % 3 == 0;
+        let both_multiples = self.length % 3 == 0 && closer.length % 3 == 0;
+        return !(either_both && multiple_of_3 && !both_multiples);
+    }
+
+    /// Whether an opener matches this run only depends on its character,
+    /// whether it can open and its length modulo 3, so closers that share
+    /// them also share the openers that are known not to match.
+    fn bottom_index(&self) -> usize {
+        let character = if self.character == '*' { 0 } else { 6 };
+        let can_open = if self.can_open { 3 } else { 0 };
+        return character + can_open + self.length % 3;
+    }
+}
+
+/// Match openers with closers, wrapping the inlines between them in
+/// emphasis. Unmatched delimiters are left as text, and so are those that
+/// would nest the inlines more than `limit` deep. `nested` has the depth of
+/// each inline that holds others, by its position. Returns the depth of the
+/// result.
+pub fn process_emphasis(inlines: &mut Vec<Inline>, delimiters: &mut [Delimiter], nested: &[(usize, usize)], limit: usize) -> usize {
+    match_delimiters(delimiters);
+    let depth = limit_depth(delimiters, nested, limit);
+
+    // Build the emphasis from the matches, with a stack of the inlines inside
+    // of each emphasis that is still open.
+    let mut stack: Vec<(Vec<Inline>, usize)> = vec![(Vec::new(), 0)];
+    let mut runs = delimiters.iter().peekable();
+    for (index, inline) in inlines.drain(..).enumerate() {
+        let run = match runs.peek() {
+            Some(run) if run.index == index => runs.next().unwrap(),
+            _ => {
+                stack.last_mut().unwrap().0.push(inline);
+                continue;
+            },
+        };
+        for _ in run.closes.iter() {
+            let (mut children, used) = stack.pop().unwrap();
+            merge_text(&mut children);
+            stack.last_mut().unwrap().0.push(match used {
+                1 => Inline::Emphasis(children),
+                _ => Inline::Strong(children),
+            });
+        }
+        let text: String = (0..run.count).map(|_| run.character).collect();
+        stack.last_mut().unwrap().0.push(Inline::Text(text));
+        for &used in run.opens.iter().rev() {
+            stack.push((Vec::new(), used));
+        }
+    }
+    *inlines = stack.pop().unwrap().0;
+    merge_text(inlines);
+    return depth;
+}
+
+/// Give the characters of emphasis that would nest the inlines more than
+/// `limit` deep back to their runs, so that they are left as text. As
+/// emphasis is deeper than any inside of it, only the outermost emphasis of
+/// a run is ever dropped. Returns the depth of the inlines.
+fn limit_depth(delimiters: &mut [Delimiter], nested: &[(usize, usize)], limit: usize) -> usize {
+
+    // The run that opened each emphasis that is still open, and the depth of
+    // the inlines inside of it so far.
+    let mut stack: Vec<(usize, usize)> = vec![(0, 0)];
+    let mut depths = nested.iter().peekable();
+    for run in 0..delimiters.len() {
+        while let Some(&&(index, depth)) = depths.peek() {
+            if index > delimiters[run].index {
+                break;
+            }
+            let open = stack.last_mut().unwrap();
+            open.1 = cmp::max(open.1, depth);
+            depths.next();
+        }
+        let mut dropped = 0;
+        for _ in 0..delimiters[run].closes.len() {
+            let (opener, depth) = stack.pop().unwrap();
+            let open = stack.last_mut().unwrap();
+            if depth >= limit {
+                let used = delimiters[opener].opens.pop().unwrap();
+                delimiters[opener].count += used;
+                dropped += 1;
+                open.1 = cmp::max(open.1, depth);
+            } else {
+                open.1 = cmp::max(open.1, depth + 1);
+            }
+        }
+        let kept = delimiters[run].closes.len() - dropped;
+        let used: usize = delimiters[run].closes.drain(kept..).sum();
+        delimiters[run].count += used;
+        for _ in 0..delimiters[run].opens.len() {
+            stack.push((run, 0));
+        }
+    }
+    let mut depth = stack[0].1;
+    for &(_, inner) in depths {
+        depth = cmp::max(depth, inner);
+    }
+    return depth;
+}
+
+/// Match up the delimiters, recording the emphasis each one opens and
+/// closes. They are kept in a linked list so that those between an opener
+/// and its closer can be dropped at once, and for each kind of closer the
+/// search for an opener stops where an earlier one of the same kind failed.
+fn match_delimiters(delimiters: &mut [Delimiter]) {
+    let length = delimiters.len();
+    let mut previous: Vec<Option<usize>> = (0..length).map(|i| i.checked_sub(1)).collect();
+    let mut next: Vec<Option<usize>> = (0..length).map(|i| Some(i + 1).filter(|&n| n < length)).collect();
+    let mut openers_bottom = [0; 12];
+    let mut closer = if length > 0 { Some(0) } else { None };
+    while let Some(c) = closer {
+        if !delimiters[c].can_close || delimiters[c].count == 0 {
+            closer = next[c];
+            continue;
+        }
+        let bottom = delimiters[c].bottom_index();
+        let mut opener = previous[c];
+        while let Some(o) = opener {
+            if o < openers_bottom[bottom] || delimiters[o].matches(&delimiters[c]) {
+                break;
+            }
+            opener = previous[o];
+        }
+        let o = match opener {
+            Some(o) if o >= openers_bottom[bottom] => o,
+            _ => {
+                openers_bottom[bottom] = c;
+                if !delimiters[c].can_open {
+                    unlink(&mut previous, &mut next, c);
+                }
+                closer = next[c];
+                continue;
+            },
+        };
+        let used = if delimiters[o].count >= 2 && delimiters[c].count >= 2 { 2 } else { 1 };
+        delimiters[o].count -= used;
+        delimiters[c].count -= used;
+        delimiters[o].opens.push(used);
+        delimiters[c].closes.push(used);
+
+        // The delimiters between the two runs can no longer be matched.
+        next[o] = Some(c);
+        previous[c] = Some(o);
+        if delimiters[o].count == 0 {
+            unlink(&mut previous, &mut next, o);
+        }
+        if delimiters[c].count == 0 {
+            closer = next[c];
+            unlink(&mut previous, &mut next, c);
+        }
+    }
+}
+
+/// Join neighbouring text, which delimiter runs leave split up.
+pub fn merge_text(inlines: &mut Vec<Inline>) {
+    let mut merged: Vec<Inline> = Vec::new();
+    for inline in inlines.drain(..) {
+        if let Inline::Text(ref text) = inline {
+            if text.is_empty() {
+                continue;
+            }
+            if let Some(&mut Inline::Text(ref mut previous)) = merged.last_mut() {
+                previous.push_str(text);
+                continue;
+            }
+        }
+        merged.push(inline);
+    }
+    *inlines = merged;
+}
+
+/// Remove a delimiter from the linked list.
+fn unlink(previous: &mut [Option<usize>], next: &mut [Option<usize>], index: usize) {
+    if let Some(before) = previous[index] {
+        next[before] = next[index];
+    }
+    if let Some(after) = next[index] {
+        previous[after] = previous[index];
+    }
+}
+
+fn is_punctuation(c: char) -> bool {
+    return c.is_ascii_punctuation() || (!c.is_ascii() && !c.is_alphanumeric() && !c.is_whitespace());
+}
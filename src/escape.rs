@@ -0,0 +1,65 @@
+//! Escaping text for HTML.
+
+use entities;
+use symbol::Symbol;
+
+/// Write text so that it can be used in HTML, either as content or as the
+/// value of an attribute.
+pub fn escape_html(output: &mut String, text: &str) {
+    for character in text.chars() {
+        match Symbol::from_char(character).char_entity() {
+            Some(entity) => output.push_str(entity),
+            None => output.push(character),
+        }
+    }
+}
+
+/// Write a URL so that it can be used as the value of an `href` or `src`
+/// attribute. Characters that are not allowed in a URL are percent encoded,
+/// but anything that is already percent encoded is left alone.
+pub fn escape_href(output: &mut String, href: &str) {
+    let bytes = href.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let byte = bytes[i];
+        match byte {
+            b'&' => output.push_str("&amp;"),
+            b'%' if i + 2 < bytes.len() && is_hex(bytes[i + 1]) && is_hex(bytes[i + 2]) => output.push('%'),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => output.push(byte as char),
+            b';' | b'/' | b'?' | b':' | b'@' | b'=' | b'+' | b'$' | b',' | b'-' | b'_' | b'.'
+            | b'!' | b'~' | b'*' | b'\'' | b'(' | b')' | b'#' => output.push(byte as char),
+            _ => output.push_str(&format!("%{:02X}", byte)),
+        }
+        i += 1;
+    }
+}
+
+fn is_hex(byte: u8) -> bool {
+    return (byte as char).is_ascii_hexdigit();
+}
+
+/// Remove backslash escapes and decode character references, as is done
+/// to link destinations and titles.
+pub fn unescape(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut output = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] == '\\' && i + 1 < chars.len() && chars[i + 1].is_ascii_punctuation() {
+            output.push(chars[i + 1]);
+            i += 2;
+            continue;
+        }
+        match entities::from_chars(&chars[i..]) {
+            Some((decoded, length)) => {
+                output.push_str(&decoded);
+                i += length;
+            },
+            None => {
+                output.push(chars[i]);
+                i += 1;
+            },
+        }
+    }
+    return output;
+}
@@ -0,0 +1,65 @@
+//! Fenced code blocks.
+
+use content::Content;
+use escape::unescape;
+use symbol::Symbol;
+
+/// The line that opens a fenced code block, such as ```` ```rust ````.
+#[derive(Clone)]
+pub struct Fence {
+    /// Either '`' or '~'.
+    pub character: char,
+    /// The number of fence characters.
+    pub length: usize,
+    /// The indentation of the opening fence, which is removed from each
+    /// line of code.
+    pub indent: usize,
+    /// What follows the opening fence, usually the language of the code.
+    pub info: String,
+}
+
+impl Fence {
+    /// Parse an opening fence on the line between `start` and `end`, where
+    /// `start` is at the given column.
+    pub fn from_line(content: &Content, start: usize, end: usize, column: usize) -> Option<Fence> {
+        let (offset, indent) = content.spacing(start, end, column);
+        if indent > 3 {
+            return None;
+        }
+        let fence_start = start + offset;
+        let symbol = match content.symbols[fence_start] {
+            Symbol::Code => Symbol::Code,
+            Symbol::Tilde => Symbol::Tilde,
+            _ => return None,
+        };
+        let length = content.sequence_length(symbol, fence_start);
+        if length < 3 {
+            return None;
+        }
+        let character = content.chars[fence_start];
+        let info = content.slice(fence_start + length, end);
+        let info = info.trim();
+
+        // The info string of a backtick fence cannot contain backticks.
+        if character == '`' && info.contains('`') {
+            return None;
+        }
+        return Some(Fence {
+            character: character,
+            length: length,
+            indent: indent,
+            info: unescape(info),
+        });
+    }
+
+    /// Check if the line between `start` and `end` closes the fence.
+    pub fn is_closed_by(&self, content: &Content, start: usize, end: usize, column: usize) -> bool {
+        let (offset, indent) = content.spacing(start, end, column);
+        if indent > 3 || content.chars.get(start + offset) != Some(&self.character) {
+            return false;
+        }
+        let length = content.sequence_length(Symbol::from_char(self.character), start + offset);
+        let (spaces, _) = content.spacing(start + offset + length, end, 0);
+        return length >= self.length && start + offset + length + spaces == end;
+    }
+}
@@ -0,0 +1,269 @@
+//! Syntax highlighting for code blocks.
+//!
+//! The highlighter is deliberately simple. It splits code into comments,
+//! strings, numbers, keywords and lifetimes and wraps each of them in a
+//! `<span>` with a matching class, leaving everything else as plain text.
+
+use escape::escape_html;
+
+struct Language {
+    keywords: &'static [&'static str],
+    line_comment: Option<&'static str>,
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Quotes that start strings. Backslash escapes only work in double
+    /// quoted strings.
+    quotes: &'static [char],
+    /// Whether three quotes in a row start a multi-line string.
+    triple_quotes: bool,
+    /// Whether the language is Rust, which has lifetimes, character
+    /// literals and raw strings.
+    rust: bool,
+}
+
+static RUST: Language = Language {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true",
+        "type", "unsafe", "use", "where", "while",
+    ],
+    line_comment: Some("//"),
+    block_comment: Some(("/*", "*/")),
+    quotes: &['"'],
+    triple_quotes: false,
+    rust: true,
+};
+
+static TOML: Language = Language {
+    keywords: &["true", "false"],
+    line_comment: Some("#"),
+    block_comment: None,
+    quotes: &['"', '\''],
+    triple_quotes: true,
+    rust: false,
+};
+
+static SHELL: Language = Language {
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in",
+        "local", "return", "then", "until", "while",
+    ],
+    line_comment: Some("#"),
+    block_comment: None,
+    quotes: &['"', '\''],
+    triple_quotes: false,
+    rust: false,
+};
+
+static JSON: Language = Language {
+    keywords: &["true", "false", "null"],
+    line_comment: None,
+    block_comment: None,
+    quotes: &['"'],
+    triple_quotes: false,
+    rust: false,
+};
+
+impl Language {
+    fn find(name: &str) -> Option<&'static Language> {
+        return match name {
+            "rust" | "rs" => Some(&RUST),
+            "toml" => Some(&TOML),
+            "sh" | "bash" | "shell" => Some(&SHELL),
+            "json" => Some(&JSON),
+            _ => None,
+        };
+    }
+}
+
+/// Write highlighted code, returning false if the language is not known.
+pub fn highlight(output: &mut String, language: &str, code: &str) -> bool {
+    let language = match Language::find(language) {
+        Some(language) => language,
+        None => return false,
+    };
+    let chars: Vec<char> = code.chars().collect();
+    let mut text = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let (class, end) = match token(language, &chars, i) {
+            Some(token) => token,
+            None => {
+
+                // Skip over the rest of a word so that numbers and keywords
+                // are only found at the start of one.
+                let mut end = i + 1;
+                if is_identifier(chars[i]) {
+                    while end < chars.len() && is_identifier(chars[end]) {
+                        end += 1;
+                    }
+                }
+                text.extend(&chars[i..end]);
+                i = end;
+                continue;
+            },
+        };
+        escape_html(output, &text);
+        text.clear();
+        let token: String = chars[i..end].iter().collect();
+        output.push_str("<span class=\"");
+        output.push_str(class);
+        output.push_str("\">");
+        escape_html(output, &token);
+        output.push_str("</span>");
+        i = end;
+    }
+    escape_html(output, &text);
+    return true;
+}
+
+/// Find the class and end of the token starting at `i`, if it is one that
+/// gets highlighted.
+fn token(language: &Language, chars: &[char], i: usize) -> Option<(&'static str, usize)> {
+    let c = chars[i];
+    if let Some(start) = language.line_comment {
+
+        // A shell comment has to start a word.
+        if starts_with(chars, i, start) && (language.rust || i == 0 || chars[i - 1].is_whitespace()) {
+            let end = (i..chars.len()).find(|&j| chars[j] == '\n').unwrap_or(chars.len());
+            return Some(("comment", end));
+        }
+    }
+    if let Some((start, end)) = language.block_comment {
+        if starts_with(chars, i, start) {
+            return Some(("comment", block_comment_end(chars, i, start, end)));
+        }
+    }
+    if language.quotes.contains(&c) {
+        return Some(("string", string_end(language, chars, i)));
+    }
+    if language.rust {
+        if let Some(token) = rust_token(chars, i) {
+            return Some(token);
+        }
+    }
+    if c.is_ascii_digit() {
+        let mut end = i + 1;
+        while end < chars.len() {
+            let next_is_digit = chars.get(end + 1).is_some_and(|next| next.is_ascii_digit());
+            if is_identifier(chars[end]) || (chars[end] == '.' && next_is_digit) {
+                end += 1;
+            } else {
+                break;
+            }
+        }
+        return Some(("number", end));
+    }
+    if is_identifier(c) {
+        let mut end = i + 1;
+        while end < chars.len() && is_identifier(chars[end]) {
+            end += 1;
+        }
+        let word: String = chars[i..end].iter().collect();
+        if language.keywords.contains(&&word[..]) {
+            return Some(("keyword", end));
+        }
+    }
+    return None;
+}
+
+/// Lifetimes, character literals, and byte and raw strings.
+fn rust_token(chars: &[char], i: usize) -> Option<(&'static str, usize)> {
+    let start = match chars[i] {
+        'b' => i + 1,
+        _ => i,
+    };
+    match chars.get(start) {
+
+        // 'a' and '\n' are characters, while 'a is a lifetime.
+        Some(&'\'') => {
+            if chars.get(start + 1) == Some(&'\\') {
+                let end = find(chars, start + 3, "'").map_or(chars.len(), |j| j + 1);
+                return Some(("string", end));
+            }
+            if chars.get(start + 2) == Some(&'\'') {
+                return Some(("string", start + 3));
+            }
+            if start == i && chars.get(i + 1).is_some_and(|&next| is_identifier(next)) {
+                let mut end = i + 2;
+                while end < chars.len() && is_identifier(chars[end]) {
+                    end += 1;
+                }
+                return Some(("lifetime", end));
+            }
+            return None;
+        },
+        Some(&'"') if start > i => return Some(("string", string_end(&RUST, chars, start))),
+
+        // r"...", r#"..."# and so on.
+        Some(&'r') => {
+            let mut end = start + 1;
+            while chars.get(end) == Some(&'#') {
+                end += 1;
+            }
+            if chars.get(end) != Some(&'"') {
+                return None;
+            }
+            let mut closing = String::from("\"");
+            for _ in start + 1..end {
+                closing.push('#');
+            }
+            let end = find(chars, end + 1, &closing).map_or(chars.len(), |j| j + closing.len());
+            return Some(("string", end));
+        },
+        _ => return None,
+    }
+}
+
+fn string_end(language: &Language, chars: &[char], i: usize) -> usize {
+    let quote = chars[i];
+    if language.triple_quotes && chars.get(i + 1) == Some(&quote) && chars.get(i + 2) == Some(&quote) {
+        let closing: String = [quote, quote, quote].iter().collect();
+        return find(chars, i + 3, &closing).map_or(chars.len(), |j| j + 3);
+    }
+    let mut j = i + 1;
+    while j < chars.len() {
+        if chars[j] == '\\' && quote == '"' {
+            j += 2;
+            continue;
+        }
+        if chars[j] == quote {
+            return j + 1;
+        }
+        j += 1;
+    }
+    return chars.len();
+}
+
+/// Find the end of a block comment, which can be nested.
+fn block_comment_end(chars: &[char], i: usize, start: &str, end: &str) -> usize {
+    let mut depth = 0;
+    let mut j = i;
+    while j < chars.len() {
+        if starts_with(chars, j, start) {
+            depth += 1;
+            j += start.len();
+        } else if starts_with(chars, j, end) {
+            depth -= 1;
+            j += end.len();
+            if depth == 0 {
+                return j;
+            }
+        } else {
+            j += 1;
+        }
+    }
+    return chars.len();
+}
+
+fn find(chars: &[char], start: usize, pattern: &str) -> Option<usize> {
+    return (start..chars.len()).find(|&j| starts_with(chars, j, pattern));
+}
+
+fn starts_with(chars: &[char], i: usize, pattern: &str) -> bool {
+    return pattern.chars().enumerate().all(|(j, c)| chars.get(i + j) == Some(&c));
+}
+
+fn is_identifier(c: char) -> bool {
+    return c.is_alphanumeric() || c == '_';
+}
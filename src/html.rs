@@ -0,0 +1,255 @@
+//! Raw HTML.
+
+use content::Content;
+
+/// Tags that start an HTML block even when the line has more after them.
+static BLOCK_TAGS: &[&str] = &[
+    "address", "article", "aside", "base", "basefont", "blockquote", "body", "caption", "center",
+    "col", "colgroup", "dd", "details", "dialog", "dir", "div", "dl", "dt", "fieldset",
+    "figcaption", "figure", "footer", "form", "frame", "frameset", "h1", "h2", "h3", "h4", "h5",
+    "h6", "head", "header", "hr", "html", "iframe", "legend", "li", "link", "main", "menu",
+    "menuitem", "nav", "noframes", "ol", "optgroup", "option", "p", "param", "search", "section",
+    "summary", "table", "tbody", "td", "tfoot", "th", "thead", "title", "tr", "track", "ul",
+];
+
+/// Tags whose content can contain blank lines.
+static RAW_TAGS: &[&str] = &["pre", "script", "style", "textarea"];
+
+/// The kinds of HTML block, which each end in their own way.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HtmlBlock {
+    /// `<pre>`, `<script>`, `<style>` or `<textarea>`, which ends at the
+    /// matching closing tag.
+    Raw,
+    /// `<!-- -->`
+    Comment,
+    /// `<? ?>`
+    ProcessingInstruction,
+    /// `<!DOCTYPE html>`
+    Declaration,
+    /// `<![CDATA[ ]]>`
+    Cdata,
+    /// A tag such as `<div>` that is known to be a block, which ends at a
+    /// blank line.
+    Block,
+    /// Any other tag alone on a line, which also ends at a blank line.
+    Tag,
+}
+
+impl HtmlBlock {
+    /// Find the kind of HTML block started by the line between `start` and
+    /// `end`, if any, where `start` is at the given column.
+    pub fn from_line(content: &Content, start: usize, end: usize, column: usize) -> Option<HtmlBlock> {
+        let (offset, indent) = content.spacing(start, end, column);
+        if indent > 3 {
+            return None;
+        }
+        let chars = &content.chars[start + offset..end];
+        if chars.first() != Some(&'<') {
+            return None;
+        }
+        if let Some(name) = tag_name(&chars[1..]) {
+            let after = chars.get(1 + name.len()).cloned().unwrap_or(' ');
+            if RAW_TAGS.contains(&&name[..]) && (after == '>' || after.is_whitespace()) {
+                return Some(HtmlBlock::Raw);
+            }
+        }
+        if starts_with(chars, "<!--") {
+            return Some(HtmlBlock::Comment);
+        }
+        if starts_with(chars, "<?") {
+            return Some(HtmlBlock::ProcessingInstruction);
+        }
+        if starts_with(chars, "<![CDATA[") {
+            return Some(HtmlBlock::Cdata);
+        }
+        if chars.len() > 2 && chars[1] == '!' && chars[2].is_ascii_alphabetic() {
+            return Some(HtmlBlock::Declaration);
+        }
+
+        // '<div', '</div', followed by a space, '>', '/>' or the end of the line.
+        let name_start = if chars.get(1) == Some(&'/') { 2 } else { 1 };
+        if let Some(name) = tag_name(&chars[name_start..]) {
+            let rest = &chars[name_start + name.len()..];
+            let ends = rest.is_empty() || rest[0] == '>' || rest[0].is_whitespace() || starts_with(rest, "/>");
+            if BLOCK_TAGS.contains(&&name[..]) && ends {
+                return Some(HtmlBlock::Block);
+            }
+        }
+
+        // Any other complete tag, as long as nothing follows it.
+        if let Some(length) = tag_length(chars) {
+            let raw = tag_name(&chars[name_start..]).is_some_and(|name| RAW_TAGS.contains(&&name[..]));
+            if !raw && chars[length..].iter().all(|c| c.is_whitespace()) {
+                return Some(HtmlBlock::Tag);
+            }
+        }
+        return None;
+    }
+
+    /// Blocks that end at a blank line cannot interrupt a paragraph, except
+    /// for known block tags.
+    pub fn interrupts_paragraph(&self) -> bool {
+        return *self != HtmlBlock::Tag;
+    }
+
+    /// Whether a blank line ends the block, rather than being part of it.
+    pub fn ends_at_blank_line(&self) -> bool {
+        return *self == HtmlBlock::Block || *self == HtmlBlock::Tag;
+    }
+
+    /// Check if the line ends the block. The line is still part of it.
+    pub fn is_ended_by(&self, line: &str) -> bool {
+        return match *self {
+            HtmlBlock::Raw => {
+                let line = line.to_ascii_lowercase();
+                RAW_TAGS.iter().any(|name| line.contains(&format!("</{}>", name)))
+            },
+            HtmlBlock::Comment => line.contains("-->"),
+            HtmlBlock::ProcessingInstruction => line.contains("?>"),
+            HtmlBlock::Declaration => line.contains('>'),
+            HtmlBlock::Cdata => line.contains("]]>"),
+            HtmlBlock::Block | HtmlBlock::Tag => false,
+        };
+    }
+}
+
+/// The kinds of inline HTML that have already been searched for their end
+/// without finding it. There cannot be an end further on either, so each is
+/// only searched for once in the same text.
+#[derive(Default)]
+pub struct Unclosed {
+    comment: bool,
+    processing_instruction: bool,
+    declaration: bool,
+    cdata: bool,
+}
+
+/// The length of the inline HTML at the start of `chars`, which can be a
+/// tag, a comment, a processing instruction, a declaration or CDATA. The
+/// same `unclosed` has to be used for the rest of the text.
+pub fn inline_length(chars: &[char], unclosed: &mut Unclosed) -> Option<usize> {
+    if starts_with(chars, "<!--") {
+
+        // '<!-->' and '<!--->' are empty comments.
+        if starts_with(&chars[4..], ">") {
+            return Some(5);
+        }
+        if starts_with(&chars[4..], "->") {
+            return Some(6);
+        }
+        return find_end(chars, 4, "-->", &mut unclosed.comment);
+    }
+    if starts_with(chars, "<?") {
+        return find_end(chars, 2, "?>", &mut unclosed.processing_instruction);
+    }
+    if starts_with(chars, "<![CDATA[") {
+        return find_end(chars, 9, "]]>", &mut unclosed.cdata);
+    }
+    if chars.len() > 2 && chars[1] == '!' && chars[2].is_ascii_alphabetic() {
+        return find_end(chars, 3, ">", &mut unclosed.declaration);
+    }
+    return tag_length(chars);
+}
+
+/// The length of the open or closing tag at the start of `chars`, such as
+/// `<a href="/">` or `</a>`.
+pub fn tag_length(chars: &[char]) -> Option<usize> {
+    if chars.first() != Some(&'<') {
+        return None;
+    }
+
+    // '</name>'
+    if chars.get(1) == Some(&'/') {
+        let name = tag_name(&chars[2..])?;
+        let i = skip_whitespace(chars, 2 + name.len());
+        return match chars.get(i) {
+            Some(&'>') => Some(i + 1),
+            _ => None,
+        };
+    }
+
+    // '<name attribute="value" />'
+    let name = tag_name(&chars[1..])?;
+    let mut i = 1 + name.len();
+    loop {
+        let next = skip_whitespace(chars, i);
+        match chars.get(next) {
+            Some(&'>') => return Some(next + 1),
+            Some(&'/') if chars.get(next + 1) == Some(&'>') => return Some(next + 2),
+
+            // Attributes have to be separated by whitespace.
+            Some(_) if next > i => i = attribute_end(chars, next)?,
+            _ => return None,
+        }
+    }
+}
+
+/// The lowercased name of a tag, which is a letter followed by letters,
+/// digits and hyphens.
+fn tag_name(chars: &[char]) -> Option<String> {
+    if !chars.first().is_some_and(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let length = chars.iter().take_while(|&&c| c.is_ascii_alphanumeric() || c == '-').count();
+    let name: String = chars[..length].iter().collect();
+    return Some(name.to_ascii_lowercase());
+}
+
+/// Find the end of an attribute such as `class="x"` starting at `start`.
+fn attribute_end(chars: &[char], start: usize) -> Option<usize> {
+    let is_name_start = |c: char| c.is_ascii_alphabetic() || c == '_' || c == ':';
+    if !chars.get(start).is_some_and(|&c| is_name_start(c)) {
+        return None;
+    }
+    let mut i = start + 1;
+    while chars.get(i).is_some_and(|&c| c.is_ascii_alphanumeric() || "_.:-".contains(c)) {
+        i += 1;
+    }
+
+    // The value is optional.
+    let equals = skip_whitespace(chars, i);
+    if chars.get(equals) != Some(&'=') {
+        return Some(i);
+    }
+    let value = skip_whitespace(chars, equals + 1);
+    return match chars.get(value) {
+        Some(&quote) if quote == '"' || quote == '\'' => {
+            let close = (value + 1..chars.len()).find(|&j| chars[j] == quote)?;
+            Some(close + 1)
+        },
+        Some(_) => {
+            let length = chars[value..].iter()
+                .take_while(|&&c| !c.is_whitespace() && !"\"'=<>`".contains(c))
+                .count();
+            if length == 0 {
+                return None;
+            }
+            Some(value + length)
+        },
+        None => None,
+    };
+}
+
+fn skip_whitespace(chars: &[char], start: usize) -> usize {
+    let mut i = start;
+    while chars.get(i).is_some_and(|c| c.is_whitespace()) {
+        i += 1;
+    }
+    return i;
+}
+
+/// Find the length up to the end of `pattern`, unless it is already known
+/// to be missing.
+fn find_end(chars: &[char], start: usize, pattern: &str, unclosed: &mut bool) -> Option<usize> {
+    if *unclosed {
+        return None;
+    }
+    let end = (start..chars.len()).find(|&i| starts_with(&chars[i..], pattern));
+    *unclosed = end.is_none();
+    return end.map(|i| i + pattern.len());
+}
+
+fn starts_with(chars: &[char], pattern: &str) -> bool {
+    return pattern.chars().enumerate().all(|(i, c)| chars.get(i) == Some(&c));
+}
@@ -1,13 +1,33 @@
 //!A native markdown parser for Rust with zero dependencies.
 
+// Explicit returns, field names and `from_str` constructors are the house style.
+#![allow(clippy::needless_return, clippy::redundant_field_names, clippy::should_implement_trait)]
+
 mod symbol;
 mod content;
+mod document;
+mod emphasis;
+mod entities;
+mod escape;
+mod fence;
+mod highlight;
+mod html;
+mod options;
+mod parser;
+mod render;
 mod section;
 mod paragraph;
 mod line;
+mod link;
+mod list;
 
 use content::Content;
 
+pub use document::{Block, Document, Inline};
+pub use options::{CodeBlockHandler, Extensions, Options, SoftBreak};
+pub use parser::{Event, Parser, Tag};
+pub use render::{HtmlRenderer, Renderer};
+
 /// Convert a string of markdown to HTML.
 ///
 /// # Examples
@@ -17,6 +37,41 @@ use content::Content;
 /// assert_eq!("<h1>Header</h1><p>Content</p>", johnmark::convert(input_str));
 /// ```
 pub fn convert(input: &str) -> String {
-    let parser = Content::from_str(input);
+    let mut parser = Content::from_str(input);
     return parser.convert();
 }
+
+/// Convert a string of markdown to HTML using the given options.
+///
+/// # Examples
+///
+/// ```
+/// use johnmark::Options;
+///
+/// let options = Options::new().xhtml(false);
+/// assert_eq!("<p>a<br>\nb</p>", johnmark::convert_with_options("a\\\nb", &options));
+/// ```
+pub fn convert_with_options(input: &str, options: &Options) -> String {
+    let mut parser = Content::with_options(input, options);
+    return parser.convert();
+}
+
+/// Parse a string of markdown into a document tree.
+///
+/// # Examples
+///
+/// ```
+/// use johnmark::{Block, Inline};
+///
+/// let document = johnmark::parse("Some *text*");
+/// assert_eq!(
+///     vec![Block::Paragraph(vec![
+///         Inline::Text("Some ".to_string()),
+///         Inline::Emphasis(vec![Inline::Text("text".to_string())]),
+///     ])],
+///     document.blocks
+/// );
+/// ```
+pub fn parse(input: &str) -> Document {
+    return Document::from_str(input);
+}
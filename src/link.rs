@@ -1,97 +1,67 @@
 //! Hyperlinks.
 
-use constants::{HTML_START_OPEN, HTML_END_OPEN, HTML_CLOSE};
+use std::cmp;
+use std::collections::HashMap;
+use std::fmt;
+
 use content::Content;
+use escape::unescape;
 use section::Section;
 use symbol::Symbol;
 
+/// Link reference definitions, keyed by their normalized label.
+pub type References = HashMap<String, LinkMetadata>;
+
+#[derive(Clone)]
 #[allow(dead_code)]
 pub struct LinkMetadata {
-    href: String,
-    title: Option<String>,
+    pub href: String,
+    pub title: Option<String>,
 }
 
 #[allow(dead_code)]
 impl LinkMetadata {
     fn new(href: &str, title: &str) -> LinkMetadata {
-        let title = match title.len() > 0 {
+        let title = match !title.is_empty() {
             true => Some(title.to_string()),
             false => None,
         };
         let href = href.to_string();
         return LinkMetadata { href: href, title: title };
     }
-    fn from_inline(content: &Content, section: &Section) -> Option<LinkMetadata> {
-        let mut link_start: usize = section.end;
-        for i in section.start..section.end {
-            match content.symbols[i] {
-                Symbol::Newline | Symbol::Space => {
-                    if i == section.end {
-                        return None;
-                    } else {
-                        continue;
-                    }
-                },
-                _ => {
-                    link_start = i;
-                    break;
-                },
-            }
-        }
-        let mut link_end: usize = section.end;
-        for i in link_start..section.end {
-            match content.symbols[i] {
-                Symbol::Newline | Symbol::Space => {
-                    link_end = i;
-                    break;
-                },
-                _ => continue,
-            }
-        }
-        if link_start == link_end {
+
+    /// Parse the destination and optional title found between the
+    /// parentheses of an inline link. The whole section has to be used.
+    pub fn from_inline(content: &Content, section: &Section) -> Option<LinkMetadata> {
+        let (metadata, metadata_end) = LinkMetadata::parse(content, section.start, section.end)?;
+        if metadata_end != section.end {
             return None;
         }
-        let href = &content.string[link_start..link_end];
-        let mut title: &str = "";
-        if link_end < section.end {
-            for i in link_end..section.end {
-                match content.symbols[i] {
-                    Symbol::Quote => {
-                        let subsection = Section::new(i + 1, section.end);
-                        title = match content.find_next("\"", &subsection) {
-                            None => "",
-                            Some(n) => &content.string[i + 1..n],
-                        };
-                        break;
-                    },
-                    Symbol::Apostrophe => {
-                        let subsection = Section::new(i + 1, section.end);
-                        title = match content.find_next("'", &subsection) {
-                            None => "",
-                            Some(n) => &content.string[i + 1..n],
-                        };
-                        break;
-                    },
-                    Symbol::LeftParenthsis => {
-                        let subsection = Section::new(i + 1, section.end);
-                        title = match content.find_next(")", &subsection) {
-                            None => "",
-                            Some(n) => &content.string[i + 1..n],
-                        };
-                        break;
-                    },
-                    _ => continue,
-                }
+        return Some(metadata);
+    }
+
+    /// Parse a destination and optional title starting at `start`, returning
+    /// them with the index after them and any blank space that follows.
+    fn parse(content: &Content, start: usize, end: usize) -> Option<(LinkMetadata, usize)> {
+        let link_start = skip_blank(content, start, end);
+        let (href, link_end) = parse_destination(content, link_start, end)?;
+        let title_start = skip_blank(content, link_end, end);
+
+        // The title has to be separated from the link.
+        if title_start > link_end && title_start < end {
+            if let Some((title, title_end)) = parse_title(content, title_start, end) {
+                return Some((LinkMetadata::new(&href, &title), skip_blank(content, title_end, end)));
             }
         }
-        return Some(LinkMetadata::new(href, title));
+        return Some((LinkMetadata::new(&href, ""), title_start));
     }
 }
 
+/// A link reference definition, such as `[id]: href "title"`.
 #[allow(dead_code)]
 pub struct Link {
-    id: String,
-    metadata: LinkMetadata
+    pub id: String,
+    pub metadata: LinkMetadata
 }
 
 #[allow(dead_code)]
@@ -101,51 +71,349 @@ impl Link {
         let metadata = LinkMetadata::new(href, title);
         return Link { id: id, metadata: metadata };
     }
-    /*
-    fn from_inline(content: &content, section: &Section) -> Option<Link> {
-        let mut id_start = section.start;
-        for i in section.start..section.end {
-            if content.symbols[i] == Symbol::LeftBracket {
-                break;
+
+    /// Parse a link reference definition at `start`. On success the index
+    /// after the line the definition ends on is returned with it.
+    pub fn from_definition(content: &Content, start: usize) -> Option<(Link, usize)> {
+        let end = content.len();
+        let mut i = start;
+        while i < end && i - start < 3 && content.symbols[i] == Symbol::Space {
+            i += 1;
+        }
+        if content.symbols[i] != Symbol::LeftBracket {
+            return None;
+        }
+
+        // '[label]:'
+        let label_start = i + 1;
+        let mut label_end = label_start;
+        loop {
+            match content.symbols[label_end] {
+                Symbol::RightBracket => break,
+                Symbol::LeftBracket | Symbol::EndInput => return None,
+                Symbol::Escape if content.symbols[label_end + 1] != Symbol::EndInput => label_end += 2,
+                _ => label_end += 1,
+            }
+        }
+        let label = content.slice(label_start, label_end);
+        if label.trim().is_empty() || label.chars().count() > 999 {
+            return None;
+        }
+        if content.chars.get(label_end + 1) != Some(&':') {
+            return None;
+        }
+
+        // The destination may be on the next line.
+        let link_start = skip_line_blank(content, label_end + 2, end);
+        if link_start == end || content.symbols[link_start].is_blank() {
+            return None;
+        }
+        let (href, link_end) = parse_destination(content, link_start, end)?;
+
+        // Without a title the definition has to end with the line.
+        let after_link = skip_spaces(content, link_end, end);
+        let untitled = match content.symbols[after_link] {
+            Symbol::Newline => Some(after_link + 1),
+            Symbol::EndInput => Some(after_link),
+            _ => None,
+        };
+
+        // The title may also be on the next line, but it has to be separated
+        // from the destination.
+        let title_start = skip_line_blank(content, link_end, end);
+        if title_start > link_end && title_start < end {
+            if let Some((title, title_end)) = parse_title(content, title_start, end) {
+                let after_title = skip_spaces(content, title_end, end);
+                let line_end = match content.symbols[after_title] {
+                    Symbol::Newline => Some(after_title + 1),
+                    Symbol::EndInput => Some(after_title),
+                    _ => None,
+                };
+                if let Some(line_end) = line_end {
+                    let link = Link { id: normalize_label(&label), metadata: LinkMetadata::new(&href, &title) };
+                    return Some((link, line_end));
+                }
+            }
+        }
+        let line_end = untitled?;
+        let link = Link { id: normalize_label(&label), metadata: LinkMetadata::new(&href, "") };
+        return Some((link, line_end));
+    }
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "<a href=\"{}\"", self.metadata.href)?;
+        if let Some(ref title) = self.metadata.title {
+            write!(f, " title=\"{}\"", title)?;
+        }
+        return write!(f, ">{}</a>", self.id);
+    }
+}
+
+/// A link found in inline content, either `[text](href "title")` or one
+/// of the reference forms: `[text][id]`, `[id][]` and `[id]`.
+pub struct InlineLink {
+    /// The link text, between the brackets.
+    pub text: Section,
+    pub metadata: LinkMetadata,
+    /// The index after the end of the link.
+    pub end: usize,
+}
+
+/// An opening bracket, which may turn out to start the text of a link or
+/// image once its closing bracket is found.
+pub struct Bracket {
+    /// The position of the bracket's text in the inlines.
+    pub index: usize,
+    /// The number of delimiter runs before the bracket.
+    pub delimiters: usize,
+    /// The index after the bracket.
+    pub start: usize,
+    pub image: bool,
+    /// Links cannot contain other links, so brackets before a link can no
+    /// longer start one.
+    pub active: bool,
+    /// How deep the links and images after the bracket are nested.
+    pub depth: usize,
+}
+
+impl Bracket {
+    pub fn new(index: usize, delimiters: usize, start: usize, image: bool) -> Bracket {
+        return Bracket {
+            index: index,
+            delimiters: delimiters,
+            start: start,
+            image: image,
+            active: true,
+            depth: 0,
+        };
+    }
+}
+
+impl InlineLink {
+    /// Look for the destination of a link whose text ends at the closing
+    /// bracket at `text.end`. It can be given in parentheses, as a label in
+    /// brackets, or by using the text itself as the label.
+    pub fn from_text(content: &Content, text: Section, end: usize) -> Option<InlineLink> {
+        let metadata_start = text.end + 1;
+
+        // '[text](href "title")'
+        if metadata_start < end && content.symbols[metadata_start] == Symbol::LeftParenthsis {
+            if let Some((metadata, metadata_end)) = LinkMetadata::parse(content, metadata_start + 1, end) {
+                if metadata_end < end && content.symbols[metadata_end] == Symbol::RightParenthsis {
+                    return Some(InlineLink { text: text, metadata: metadata, end: metadata_end + 1 });
+                }
+            }
+        }
+
+        // '[text][id]' and '[id][]'. A label is at most 999 characters, so
+        // there is no need to look any further for its closing bracket.
+        if metadata_start < end && content.symbols[metadata_start] == Symbol::LeftBracket {
+            let search_end = cmp::min(end, metadata_start + 1001);
+            let label_end = (metadata_start + 1..search_end).find(|&i| {
+                (content.symbols[i] == Symbol::RightBracket || content.symbols[i] == Symbol::LeftBracket)
+                    && !content.is_escaped(i)
+            })?;
+            if content.symbols[label_end] != Symbol::RightBracket {
+                return None;
             }
-            id_start += 1;
+            let metadata = match label_end == metadata_start + 1 {
+                true => find_reference(content, text.start, text.end)?,
+                false => find_reference(content, metadata_start + 1, label_end)?,
+            };
+            return Some(InlineLink { text: text, metadata: metadata, end: label_end + 1 });
         }
-        let id_end = match content.find_next("](", section) {
-            None => return None,
-            Some(i) => i,
+
+        // '[id]'
+        let metadata = find_reference(content, text.start, text.end)?;
+        return Some(InlineLink { text: text, metadata: metadata, end: metadata_start });
+    }
+
+    /// Look for an autolink, `<scheme:foo>` or `<foo@example.com>`, starting
+    /// at the angle bracket at the start of the section. Its text is the
+    /// same as its destination, aside from the `mailto:` given to emails.
+    pub fn from_autolink(content: &Content, section: &Section) -> Option<InlineLink> {
+        let start = section.start + 1;
+        let close = (start..section.end).find(|&i| {
+            content.symbols[i] == Symbol::GreaterThan || content.symbols[i] == Symbol::LessThan
+        })?;
+        if content.symbols[close] != Symbol::GreaterThan {
+            return None;
+        }
+        let text = content.slice(start, close);
+        let href = if is_uri(&text) {
+            text
+        } else if is_email(&text) {
+            format!("mailto:{}", text)
+        } else {
+            return None;
         };
-        let id = &content.string[id_start..id_end];
-
-    }
-    */
-    fn to_string(&self) -> String {
-        let mut output = String::new();
-        output.push_str(HTML_START_OPEN);
-        output.push_str("a href=\"");
-        output.push_str(&self.metadata.href);
-        output.push('"');
-        match self.metadata.title {
-            None => output.push_str(HTML_CLOSE),
-            Some(ref title) => {
-                output.push_str(" title=\"");
-                output.push_str(title);
-                output.push('"');
-                output.push_str(HTML_CLOSE);
+        let metadata = LinkMetadata::new(&href, "");
+        return Some(InlineLink { text: Section::new(start, close), metadata: metadata, end: close + 1 });
+    }
+}
+
+/// Look up the link reference definition for the label between `start` and
+/// `end`.
+fn find_reference(content: &Content, start: usize, end: usize) -> Option<LinkMetadata> {
+    if end - start > 999 {
+        return None;
+    }
+    return content.references.get(&normalize_label(&content.slice(start, end))).cloned();
+}
+
+/// Check for an absolute URI: a scheme of 2 to 32 characters, a colon and
+/// then anything other than spaces, controls and angle brackets.
+fn is_uri(text: &str) -> bool {
+    let colon = match text.find(':') {
+        None => return false,
+        Some(i) => i,
+    };
+    let scheme = &text[..colon];
+    if scheme.len() < 2 || scheme.len() > 32 {
+        return false;
+    }
+    let mut characters = scheme.chars();
+    if !characters.next().is_some_and(|c| c.is_ascii_alphabetic()) {
+        return false;
+    }
+    if !characters.all(|c| c.is_ascii_alphanumeric() || c == '+' || c == '.' || c == '-') {
+        return false;
+    }
+    return text[colon + 1..].chars().all(|c| !c.is_ascii_control() && c != ' ' && c != '<' && c != '>');
+}
+
+/// Check for an email address in the form HTML5 allows.
+fn is_email(text: &str) -> bool {
+    let at = match text.find('@') {
+        None => return false,
+        Some(i) => i,
+    };
+    let local = &text[..at];
+    if local.is_empty() || !local.chars().all(|c| c.is_ascii_alphanumeric() || ".!#$%&'*+/=?^_`{|}~-".contains(c)) {
+        return false;
+    }
+
+    // Each label of the domain is up to 63 letters, digits and hyphens, but
+    // cannot start or end with a hyphen.
+    return text[at + 1..].split('.').all(|label| {
+        !label.is_empty() && label.len() <= 63
+            && !label.starts_with('-') && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    });
+}
+
+/// Normalize a link label so that labels can be compared: case is folded
+/// and runs of whitespace become a single space.
+pub fn normalize_label(label: &str) -> String {
+    let words: Vec<&str> = label.split_whitespace().collect();
+    return words.join(" ").to_lowercase().to_uppercase();
+}
+
+/// Parse a link destination, either `<foo bar>` or `foo(bar)`, returning it
+/// with the index after it.
+fn parse_destination(content: &Content, start: usize, end: usize) -> Option<(String, usize)> {
+    if start < end && content.symbols[start] == Symbol::LessThan {
+        let mut i = start + 1;
+        loop {
+            if i >= end {
+                return None;
+            }
+            match content.symbols[i] {
+                Symbol::GreaterThan => break,
+                Symbol::LessThan | Symbol::Newline => return None,
+                Symbol::Escape if i + 1 < end => i += 2,
+                _ => i += 1,
             }
         }
-        output.push_str(&self.id);
-        output.push_str(HTML_END_OPEN);
-        output.push_str("a");
-        output.push_str(HTML_CLOSE);
-        return output;
+        return Some((unescape(&content.slice(start + 1, i)), i + 1));
     }
+
+    // The parentheses in a destination have to be balanced, so an extra
+    // closing parenthesis ends it. Like other parsers, only a limited depth
+    // is allowed, so that a long run of opening parentheses is not searched
+    // again for each link.
+    let mut depth = 0;
+    let mut i = start;
+    while i < end && !content.symbols[i].is_blank() {
+        match content.symbols[i] {
+            Symbol::Escape if i + 1 < end && !content.symbols[i + 1].is_blank() => i += 1,
+            Symbol::LeftParenthsis => {
+                depth += 1;
+                if depth > 32 {
+                    return None;
+                }
+            },
+            Symbol::RightParenthsis => {
+                if depth == 0 {
+                    break;
+                }
+                depth -= 1;
+            },
+            _ => {},
+        }
+        i += 1;
+    }
+    if depth != 0 {
+        return None;
+    }
+    return Some((unescape(&content.slice(start, i)), i));
+}
+
+/// Parse a link title in quotes or parentheses, returning it with the index
+/// after it.
+fn parse_title(content: &Content, start: usize, end: usize) -> Option<(String, usize)> {
+    let closing = match content.symbols[start] {
+        Symbol::Quote => Symbol::Quote,
+        Symbol::Apostrophe => Symbol::Apostrophe,
+        Symbol::LeftParenthsis => Symbol::RightParenthsis,
+        _ => return None,
+    };
+
+    // A title in parentheses cannot contain an unescaped opening one.
+    let title_end = (start + 1..end).find(|&i| {
+        let nested = closing == Symbol::RightParenthsis && content.symbols[i] == Symbol::LeftParenthsis;
+        (content.symbols[i] == closing || nested) && !content.is_escaped(i)
+    })?;
+    if content.symbols[title_end] != closing {
+        return None;
+    }
+    return Some((unescape(&content.slice(start + 1, title_end)), title_end + 1));
+}
+
+/// Skip spaces and tabs.
+fn skip_spaces(content: &Content, start: usize, end: usize) -> usize {
+    let mut i = start;
+    while i < end && (content.symbols[i] == Symbol::Space || content.symbols[i] == Symbol::Tab) {
+        i += 1;
+    }
+    return i;
+}
+
+/// Skip spaces and tabs, along with at most one line ending.
+fn skip_line_blank(content: &Content, start: usize, end: usize) -> usize {
+    let i = skip_spaces(content, start, end);
+    if i < end && content.symbols[i] == Symbol::Newline {
+        return skip_spaces(content, i + 1, end);
+    }
+    return i;
+}
+
+/// Find the first character that is not blank.
+fn skip_blank(content: &Content, start: usize, end: usize) -> usize {
+    let mut i = start;
+    while i < end && content.symbols[i].is_blank() {
+        i += 1;
+    }
+    return i;
 }
 
 #[cfg(test)]
 mod test {
     use super::Link;
     use super::LinkMetadata;
-    use content::content;
+    use content::Content;
     use section::Section;
 
     #[test]
@@ -197,4 +465,21 @@ mod test {
         assert_eq!(content_str, link_metadata.href);
         assert_eq!(Some("Title".to_string()), link_metadata.title);
     }
+
+    #[test]
+    fn parse_link_metadata_parentheses() {
+        let content_str = "foo(and(bar)) \"Title (with parentheses)\"";
+        let content = Content::from_str(content_str);
+        let section = Section::new(0, content_str.len());
+        let link_metadata = match LinkMetadata::from_inline(&content, &section) {
+            None => panic!("Parsing error."),
+            Some(m) => m,
+        };
+        assert_eq!("foo(and(bar))", link_metadata.href);
+        assert_eq!(Some("Title (with parentheses)".to_string()), link_metadata.title);
+
+        // Unbalanced.
+        let content = Content::from_str("foo(bar");
+        assert!(LinkMetadata::from_inline(&content, &Section::new(0, 7)).is_none());
+    }
 }
@@ -0,0 +1,110 @@
+//! Lists.
+
+use content::Content;
+use symbol::Symbol;
+
+/// The marker at the start of a list item, such as `- ` or `1. `.
+#[derive(Clone, Copy)]
+pub struct ListMarker {
+    /// The character used as the bullet, or the one after the number of an
+    /// ordered item.
+    pub bullet: char,
+    /// The number of an ordered item.
+    pub start: Option<usize>,
+    /// The column the content of the item starts at. Lines indented at
+    /// least this far belong to the item.
+    pub indent: usize,
+    /// The index where the content on the first line starts. It can be a
+    /// tab that the padding only uses part of.
+    pub content: usize,
+    /// Whether the first line has nothing after the marker.
+    pub empty: bool,
+}
+
+impl ListMarker {
+    /// Parse the marker at the start of the line between `start` and `end`,
+    /// where `start` is at the given column. Columns are counted from it.
+    pub fn from_line(content: &Content, start: usize, end: usize, column: usize) -> Option<ListMarker> {
+        let (offset, columns) = content.spacing(start, end, column);
+        if columns > 3 {
+            return None;
+        }
+        let (start_number, width) = match content.symbols[start + offset] {
+            Symbol::Asterisk | Symbol::Hyphen | Symbol::Plus => (None, 1),
+            _ => {
+                let (number, digits) = parse_number(content, start + offset, end)?;
+                match content.chars.get(start + offset + digits) {
+                    Some(&'.') | Some(&')') => (Some(number), digits + 1),
+                    _ => return None,
+                }
+            },
+        };
+        let marker = start + offset + width - 1;
+        let bullet = content.chars[marker];
+        let columns = columns + width - 1;
+        let (padding, padding_columns) = content.spacing(marker + 1, end, column + columns + 1);
+
+        // The marker has to be followed by a space unless the line ends.
+        if padding == 0 && marker + 1 < end {
+            return None;
+        }
+
+        // An item that starts with a blank line, or with indented code,
+        // only uses a single space of padding.
+        if marker + 1 + padding == end {
+            return Some(ListMarker {
+                bullet: bullet,
+                start: start_number,
+                indent: columns + 2,
+                content: end,
+                empty: true,
+            });
+        }
+        if padding_columns > 4 {
+            return Some(ListMarker {
+                bullet: bullet,
+                start: start_number,
+                indent: columns + 2,
+                content: content.skip_indentation(marker + 1, end, column + columns + 1, 1).0,
+                empty: false,
+            });
+        }
+        return Some(ListMarker {
+            bullet: bullet,
+            start: start_number,
+            indent: columns + 1 + padding_columns,
+            content: marker + 1 + padding,
+            empty: false,
+        });
+    }
+
+    /// Check if an item with this marker can follow an item with the other
+    /// one in the same list.
+    pub fn continues(&self, other: &ListMarker) -> bool {
+        return self.bullet == other.bullet && self.start.is_some() == other.start.is_some();
+    }
+
+    /// Check if a list starting with this marker can interrupt a paragraph.
+    /// Only items with content can, and ordered lists have to start at 1.
+    pub fn interrupts_paragraph(&self) -> bool {
+        return !self.empty && self.start.unwrap_or(1) == 1;
+    }
+}
+
+/// Parse the digits of an ordered list marker, returning the number and how
+/// many digits it has. Markers are at most nine digits long.
+fn parse_number(content: &Content, start: usize, end: usize) -> Option<(usize, usize)> {
+    let mut number = 0;
+    let mut index = start;
+    while index < end && content.chars[index].is_ascii_digit() {
+        number = number * 10 + content.chars[index].to_digit(10)? as usize;
+        index += 1;
+        if index - start > 9 {
+            return None;
+        }
+    }
+    if index == start {
+        return None;
+    }
+    return Some((number, index - start));
+}
@@ -0,0 +1,247 @@
+//! Parsing and rendering options.
+
+use std::fmt;
+use std::sync::Arc;
+
+/// Syntax that is not part of CommonMark. Everything is off by default,
+/// and there are no extensions yet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Extensions {}
+
+impl Extensions {
+    pub const fn none() -> Extensions {
+        return Extensions {};
+    }
+
+    pub const fn all() -> Extensions {
+        return Extensions {};
+    }
+}
+
+impl Default for Extensions {
+    fn default() -> Extensions {
+        return Extensions::none();
+    }
+}
+
+/// How line endings inside of a paragraph are rendered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SoftBreak {
+    /// Keep the line ending.
+    Newline,
+    /// Join the lines with a space.
+    Space,
+    /// Render a line break, as in GitHub comments.
+    Break,
+}
+
+/// Turns the code of a fenced block into HTML. It is given the code and the
+/// whole info string. Handlers are shared between clones of `Options`, so
+/// they can capture configuration.
+pub type CodeBlockHandler = Arc<dyn Fn(&str, &str) -> String + Send + Sync>;
+
+/// Options used when parsing and rendering markdown.
+///
+/// # Examples
+///
+/// ```
+/// use johnmark::Options;
+///
+/// let options = Options::new().xhtml(false).pretty(true);
+/// assert_eq!("<hr>\n", johnmark::convert_with_options("***", &options));
+/// ```
+#[derive(Clone)]
+pub struct Options {
+    pub extensions: Extensions,
+    /// Close void elements XHTML style (`<hr />`) rather than HTML5 style
+    /// (`<hr>`).
+    pub xhtml: bool,
+    /// The columns between tab stops. Tabs are only expanded when they are
+    /// part of the indentation that decides the structure of the blocks,
+    /// and anywhere else they are kept as they are.
+    pub tab_width: usize,
+    /// Treat any HTML in the input as text, and leave out URLs that can run
+    /// scripts.
+    pub safe: bool,
+    /// Put each block on its own line.
+    pub pretty: bool,
+    pub soft_break: SoftBreak,
+    /// Highlight Rust, TOML, shell and JSON code blocks with `<span>` tags.
+    pub highlight: bool,
+    /// Handlers for code blocks, keyed by the language in their info string.
+    pub code_block_handlers: Vec<(String, CodeBlockHandler)>,
+}
+
+impl Options {
+    pub const fn new() -> Options {
+        return Options {
+            extensions: Extensions::none(),
+            xhtml: true,
+            tab_width: 4,
+            safe: false,
+            pretty: false,
+            soft_break: SoftBreak::Newline,
+            highlight: false,
+            code_block_handlers: Vec::new(),
+        };
+    }
+
+    pub fn extensions(mut self, extensions: Extensions) -> Options {
+        self.extensions = extensions;
+        return self;
+    }
+
+    pub fn xhtml(mut self, xhtml: bool) -> Options {
+        self.xhtml = xhtml;
+        return self;
+    }
+
+    pub fn tab_width(mut self, tab_width: usize) -> Options {
+        self.tab_width = tab_width;
+        return self;
+    }
+
+    /// Make the output safe to embed when the input cannot be trusted, as
+    /// with user comments. Raw HTML is escaped, and `javascript:`,
+    /// `vbscript:`, `file:` and non-image `data:` URLs become empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use johnmark::Options;
+    ///
+    /// let options = Options::new().safe(true);
+    /// assert_eq!(
+    ///     "<p>&lt;script&gt;alert(1)&lt;/script&gt;</p><p><a href=\"\">click</a></p>",
+    ///     johnmark::convert_with_options("<script>alert(1)</script>\n\n[click](javascript:alert(1))", &options)
+    /// );
+    /// ```
+    pub fn safe(mut self, safe: bool) -> Options {
+        self.safe = safe;
+        return self;
+    }
+
+    pub fn pretty(mut self, pretty: bool) -> Options {
+        self.pretty = pretty;
+        return self;
+    }
+
+    /// Choose how line endings inside of a paragraph are rendered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use johnmark::{Options, SoftBreak};
+    ///
+    /// let options = Options::new().soft_break(SoftBreak::Break);
+    /// assert_eq!("<p>foo<br />\nbar</p>", johnmark::convert_with_options("foo\nbar", &options));
+    /// ```
+    pub fn soft_break(mut self, soft_break: SoftBreak) -> Options {
+        self.soft_break = soft_break;
+        return self;
+    }
+
+    /// Wrap the keywords, strings, comments, lifetimes and numbers of
+    /// Rust, TOML, shell and JSON code blocks in `<span>` tags with those
+    /// names as their classes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use johnmark::Options;
+    ///
+    /// let options = Options::new().highlight(true);
+    /// assert_eq!(
+    ///     "<pre><code class=\"language-rust\"><span class=\"keyword\">let</span> x = \
+    ///      <span class=\"number\">1</span>;\n</code></pre>",
+    ///     johnmark::convert_with_options("```rust\nlet x = 1;\n```", &options)
+    /// );
+    /// ```
+    pub fn highlight(mut self, highlight: bool) -> Options {
+        self.highlight = highlight;
+        return self;
+    }
+
+    /// Render code blocks in the given language with a handler instead of
+    /// as `<pre><code>`. A later handler for the same language replaces an
+    /// earlier one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use johnmark::Options;
+    ///
+    /// let class = "diagram";
+    /// let options = Options::new().code_block_handler("mermaid", move |code, _info| {
+    ///     return format!("<div class=\"{}\">{}</div>", class, code);
+    /// });
+    /// assert_eq!(
+    ///     "<div class=\"diagram\">graph TD;\n</div>",
+    ///     johnmark::convert_with_options("```mermaid\ngraph TD;\n```", &options)
+    /// );
+    /// ```
+    pub fn code_block_handler<F>(mut self, language: &str, handler: F) -> Options
+        where F: Fn(&str, &str) -> String + Send + Sync + 'static {
+        self.code_block_handlers.retain(|(name, _)| name != language);
+        self.code_block_handlers.push((language.to_string(), Arc::new(handler)));
+        return self;
+    }
+
+    /// Find the handler for code blocks in a language.
+    pub fn find_code_block_handler(&self, language: &str) -> Option<CodeBlockHandler> {
+        return self.code_block_handlers.iter()
+            .find(|&(name, _)| name == language)
+            .map(|(_, handler)| handler.clone());
+    }
+}
+
+impl fmt::Debug for Options {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+
+        // Handlers can't be printed, so only their languages are shown.
+        let languages: Vec<&str> = self.code_block_handlers.iter()
+            .map(|(name, _)| &name[..])
+            .collect();
+        return f.debug_struct("Options")
+            .field("extensions", &self.extensions)
+            .field("xhtml", &self.xhtml)
+            .field("tab_width", &self.tab_width)
+            .field("safe", &self.safe)
+            .field("pretty", &self.pretty)
+            .field("soft_break", &self.soft_break)
+            .field("highlight", &self.highlight)
+            .field("code_block_handlers", &languages)
+            .finish();
+    }
+}
+
+impl PartialEq for Options {
+    fn eq(&self, other: &Options) -> bool {
+
+        // Handlers are only equal when they are the same shared closure.
+        let handlers_eq = self.code_block_handlers.len() == other.code_block_handlers.len()
+            && self.code_block_handlers.iter()
+                .zip(other.code_block_handlers.iter())
+                .all(|((name, handler), (other_name, other_handler))| {
+                    name == other_name && Arc::ptr_eq(handler, other_handler)
+                });
+        return self.extensions == other.extensions
+            && self.xhtml == other.xhtml
+            && self.tab_width == other.tab_width
+            && self.safe == other.safe
+            && self.pretty == other.pretty
+            && self.soft_break == other.soft_break
+            && self.highlight == other.highlight
+            && handlers_eq;
+    }
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        return Options::new();
+    }
+}
+
+/// The options used when none are given.
+pub static DEFAULT_OPTIONS: Options = Options::new();
@@ -1,15 +1,112 @@
 use section::Section;
 use content::LineType;
+use fence::Fence;
+use html::HtmlBlock;
+use list::ListMarker;
 
+/// A block of the input. Leaf blocks such as paragraphs keep their lines,
+/// while blockquotes, lists and list items hold other blocks.
 #[allow(dead_code)]
 pub struct Paragraph {
     pub lines: Vec<Section>,
-    pub kind: LineType
+    pub kind: LineType,
+    pub children: Vec<Paragraph>,
+    /// The marker of a list or a list item.
+    pub marker: Option<ListMarker>,
+    /// The opening fence of fenced code.
+    pub fence: Option<Fence>,
+    /// The kind of an HTML block, which decides where it ends.
+    pub html: Option<HtmlBlock>,
+    /// Whether a list is tight. This is only known once it is closed.
+    pub tight: bool,
+    /// Whether lines can still be added to the block.
+    pub open: bool,
+    /// Whether the last line added to the block was blank.
+    pub last_line_blank: bool,
 }
 
 #[allow(dead_code)]
 impl Paragraph {
     pub fn new(lines: Vec<Section>, kind: LineType) -> Paragraph {
-        return Paragraph { lines: lines, kind: kind };
+        return Paragraph {
+            lines: lines,
+            kind: kind,
+            children: Vec::new(),
+            marker: None,
+            fence: None,
+            html: None,
+            tight: true,
+            open: true,
+            last_line_blank: false,
+        };
+    }
+
+    /// The last child, if it is still open.
+    pub fn open_child(&self) -> Option<&Paragraph> {
+        return self.children.last().filter(|child| child.open);
+    }
+
+    /// The number of open blocks inside of this one.
+    pub fn open_depth(&self) -> usize {
+        return match self.open_child() {
+            Some(child) => child.open_depth() + 1,
+            None => 0,
+        };
+    }
+
+    /// The open block `depth` levels down.
+    pub fn descendant(&mut self, depth: usize) -> &mut Paragraph {
+        let mut paragraph = self;
+        for _ in 0..depth {
+            paragraph = paragraph.children.last_mut().expect("Missing open block.");
+        }
+        return paragraph;
+    }
+
+    /// The kind of the deepest open block.
+    pub fn tip(&self) -> LineType {
+        return match self.open_child() {
+            Some(child) => child.tip(),
+            None => self.kind,
+        };
+    }
+
+    pub fn can_contain(&self, kind: LineType) -> bool {
+        return match self.kind {
+            LineType::Null | LineType::Blockquote | LineType::Item => kind != LineType::Item,
+            LineType::List => kind == LineType::Item,
+            _ => false,
+        };
+    }
+
+    /// Check if the block ends with a blank line, looking inside of lists.
+    pub fn ends_with_blank_line(&self) -> bool {
+        if self.last_line_blank {
+            return true;
+        }
+        return match self.kind {
+            LineType::List | LineType::Item => {
+                self.children.last().is_some_and(|child| child.ends_with_blank_line())
+            },
+            _ => false,
+        };
+    }
+
+    /// A list is loose when its items, or the blocks inside of an item, are
+    /// separated by blank lines.
+    pub fn is_tight(&self) -> bool {
+        for (i, item) in self.children.iter().enumerate() {
+            let last_item = i + 1 == self.children.len();
+            if item.ends_with_blank_line() && !last_item {
+                return false;
+            }
+            for (j, child) in item.children.iter().enumerate() {
+                let last_child = j + 1 == item.children.len();
+                if child.ends_with_blank_line() && !(last_item && last_child) {
+                    return false;
+                }
+            }
+        }
+        return true;
     }
 }
@@ -0,0 +1,176 @@
+//! A pull parser.
+//!
+//! The parser reads the whole input and builds its block structure up
+//! front, since link reference definitions can appear anywhere in it. Only
+//! the inline content of each top-level section is parsed lazily, the first
+//! time its events are asked for, and no HTML is ever rendered.
+
+use std::collections::VecDeque;
+use std::vec;
+
+use content::Content;
+use document::{Block, Inline};
+use options::Options;
+use paragraph::Paragraph;
+
+/// A node that wraps other events.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Tag {
+    Paragraph,
+    Heading(usize),
+    BlockQuote,
+    /// A code block with its info string.
+    CodeBlock(String),
+    /// A list with the number of its first item if it is ordered.
+    List(Option<usize>),
+    Item,
+    Emphasis,
+    Strong,
+    /// A link with its destination and optional title.
+    Link(String, Option<String>),
+    /// An image with its source and optional title. The events inside make
+    /// up its description.
+    Image(String, Option<String>),
+}
+
+/// A single step through a document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Start(Tag),
+    End(Tag),
+    Text(String),
+    /// An inline code span.
+    Code(String),
+    SoftBreak,
+    HardBreak,
+    /// A thematic break.
+    Rule,
+    /// Raw HTML.
+    Html(String),
+}
+
+/// An iterator over the events of a markdown document.
+///
+/// # Examples
+///
+/// ```
+/// use johnmark::{Event, Parser, Tag};
+///
+/// let events: Vec<Event> = Parser::new("# Header").collect();
+/// assert_eq!(
+///     vec![
+///         Event::Start(Tag::Heading(1)),
+///         Event::Text("Header".to_string()),
+///         Event::End(Tag::Heading(1)),
+///     ],
+///     events
+/// );
+/// ```
+pub struct Parser {
+    content: Content,
+    sections: vec::IntoIter<Paragraph>,
+    events: VecDeque<Event>,
+}
+
+impl Parser {
+    pub fn new(input: &str) -> Parser {
+        return Parser::with_options(input, &Options::new());
+    }
+
+    /// Create a parser with the given options. The block structure of the
+    /// whole input is built here, and inlines are parsed as they are reached.
+    pub fn with_options(input: &str, options: &Options) -> Parser {
+        let mut content = Content::with_options(input, options);
+        let sections = content.sections();
+        content.add_references(&sections);
+        let sections = sections.into_iter();
+        return Parser { content: content, sections: sections, events: VecDeque::new() };
+    }
+}
+
+impl Iterator for Parser {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Some(event);
+            }
+            let section = self.sections.next()?;
+            for block in self.content.convert_section(&section) {
+                push_block(&mut self.events, block);
+            }
+        }
+    }
+}
+
+fn push_block(events: &mut VecDeque<Event>, block: Block) {
+    match block {
+        Block::Heading { level, content } => {
+            events.push_back(Event::Start(Tag::Heading(level)));
+            push_inlines(events, content);
+            events.push_back(Event::End(Tag::Heading(level)));
+        },
+        Block::Paragraph(content) => {
+            events.push_back(Event::Start(Tag::Paragraph));
+            push_inlines(events, content);
+            events.push_back(Event::End(Tag::Paragraph));
+        },
+        Block::CodeBlock { info, literal } => {
+            events.push_back(Event::Start(Tag::CodeBlock(info.clone())));
+            events.push_back(Event::Text(literal));
+            events.push_back(Event::End(Tag::CodeBlock(info)));
+        },
+        Block::HtmlBlock(literal) => events.push_back(Event::Html(literal)),
+        Block::BlockQuote(children) => {
+            events.push_back(Event::Start(Tag::BlockQuote));
+            for child in children {
+                push_block(events, child);
+            }
+            events.push_back(Event::End(Tag::BlockQuote));
+        },
+        Block::List { start, tight, items } => {
+            events.push_back(Event::Start(Tag::List(start)));
+            for item in items {
+                events.push_back(Event::Start(Tag::Item));
+                for child in item {
+                    match child {
+
+                        // Tight lists leave out the paragraph around the text.
+                        Block::Paragraph(content) if tight => push_inlines(events, content),
+                        _ => push_block(events, child),
+                    }
+                }
+                events.push_back(Event::End(Tag::Item));
+            }
+            events.push_back(Event::End(Tag::List(start)));
+        },
+        Block::ThematicBreak => events.push_back(Event::Rule),
+    }
+}
+
+fn push_inlines(events: &mut VecDeque<Event>, inlines: Vec<Inline>) {
+    for inline in inlines {
+        match inline {
+            Inline::Text(text) => events.push_back(Event::Text(text)),
+            Inline::Code(code) => events.push_back(Event::Code(code)),
+            Inline::Emphasis(content) => push_container(events, Tag::Emphasis, content),
+            Inline::Strong(content) => push_container(events, Tag::Strong, content),
+            Inline::Link { destination, title, content } => {
+                push_container(events, Tag::Link(destination, title), content);
+            },
+            Inline::Image { destination, title, content } => {
+                push_container(events, Tag::Image(destination, title), content);
+            },
+            Inline::Html(html) => events.push_back(Event::Html(html)),
+            Inline::SoftBreak => events.push_back(Event::SoftBreak),
+            Inline::HardBreak => events.push_back(Event::HardBreak),
+        }
+    }
+}
+
+fn push_container(events: &mut VecDeque<Event>, tag: Tag, content: Vec<Inline>) {
+    events.push_back(Event::Start(tag.clone()));
+    push_inlines(events, content);
+    events.push_back(Event::End(tag));
+}
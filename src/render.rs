@@ -0,0 +1,360 @@
+//! Rendering.
+//!
+//! A `Renderer` has one hook for each kind of node in the document tree. The
+//! hooks default to HTML output, so a renderer only has to override the
+//! nodes it wants to change.
+
+use document::{Block, Document, Inline};
+use escape::{escape_href, escape_html};
+use highlight::highlight;
+use options::{Options, SoftBreak, DEFAULT_OPTIONS};
+
+/// Turn a document into a string.
+///
+/// # Examples
+///
+/// ```
+/// use johnmark::{Document, Inline, Renderer};
+///
+/// struct Classy;
+///
+/// impl Renderer for Classy {
+///     fn heading(&mut self, output: &mut String, level: usize, content: &[Inline]) {
+///         output.push_str(&format!("<h{} class=\"title\">", level));
+///         self.inlines(output, content);
+///         output.push_str(&format!("</h{}>", level));
+///     }
+/// }
+///
+/// let document = Document::from_str("# Header\n\nContent");
+/// assert_eq!("<h1 class=\"title\">Header</h1><p>Content</p>", Classy.render(&document));
+/// ```
+pub trait Renderer {
+    /// The options the default hooks follow.
+    fn options(&self) -> &Options {
+        return &DEFAULT_OPTIONS;
+    }
+
+    fn render(&mut self, document: &Document) -> String {
+        let mut output = String::new();
+        self.blocks(&mut output, &document.blocks);
+        return output;
+    }
+
+    // DISPATCH //
+
+    fn blocks(&mut self, output: &mut String, blocks: &[Block]) {
+        for block in blocks.iter() {
+            self.block(output, block);
+        }
+    }
+
+    fn block(&mut self, output: &mut String, block: &Block) {
+        match *block {
+            Block::Heading { level, ref content } => self.heading(output, level, content),
+            Block::Paragraph(ref content) => self.paragraph(output, content),
+            Block::CodeBlock { ref info, ref literal } => self.code_block(output, info, literal),
+            Block::HtmlBlock(ref literal) => self.html_block(output, literal),
+            Block::BlockQuote(ref children) => self.block_quote(output, children),
+            Block::List { start, tight, ref items } => self.list(output, start, tight, items),
+            Block::ThematicBreak => self.thematic_break(output),
+        }
+    }
+
+    fn inlines(&mut self, output: &mut String, inlines: &[Inline]) {
+        for inline in inlines.iter() {
+            self.inline(output, inline);
+        }
+    }
+
+    fn inline(&mut self, output: &mut String, inline: &Inline) {
+        match *inline {
+            Inline::Text(ref text) => self.text(output, text),
+            Inline::Code(ref code) => self.code(output, code),
+            Inline::Emphasis(ref content) => self.emphasis(output, content),
+            Inline::Strong(ref content) => self.strong(output, content),
+            Inline::Link { ref destination, ref title, ref content } => {
+                self.link(output, destination, title.as_ref().map(|t| &t[..]), content);
+            },
+            Inline::Image { ref destination, ref title, ref content } => {
+                self.image(output, destination, title.as_ref().map(|t| &t[..]), content);
+            },
+            Inline::Html(ref html) => self.html(output, html),
+            Inline::SoftBreak => self.soft_break(output),
+            Inline::HardBreak => self.hard_break(output),
+        }
+    }
+
+    // BLOCKS //
+
+    fn heading(&mut self, output: &mut String, level: usize, content: &[Inline]) {
+        output.push_str(&format!("<h{}>", level));
+        self.inlines(output, content);
+        output.push_str(&format!("</h{}>", level));
+        self.block_end(output);
+    }
+
+    fn paragraph(&mut self, output: &mut String, content: &[Inline]) {
+        output.push_str("<p>");
+        self.inlines(output, content);
+        output.push_str("</p>");
+        self.block_end(output);
+    }
+
+    fn code_block(&mut self, output: &mut String, info: &str, literal: &str) {
+
+        // The first word of the info string is the language.
+        let language = info.split_whitespace().next();
+        if let Some(handler) = language.and_then(|language| self.options().find_code_block_handler(language)) {
+            output.push_str(&handler(literal, info));
+            self.block_end(output);
+            return;
+        }
+        output.push_str("<pre><code");
+        if let Some(language) = language {
+            output.push_str(" class=\"language-");
+            escape_html(output, language);
+            output.push('"');
+        }
+        output.push('>');
+        let highlighted = match language {
+            Some(language) if self.options().highlight => highlight(output, language, literal),
+            _ => false,
+        };
+        if !highlighted {
+            escape_html(output, literal);
+        }
+        output.push_str("</code></pre>");
+        self.block_end(output);
+    }
+
+    fn block_quote(&mut self, output: &mut String, children: &[Block]) {
+        output.push_str("<blockquote>");
+        self.block_end(output);
+        self.blocks(output, children);
+        output.push_str("</blockquote>");
+        self.block_end(output);
+    }
+
+    fn list(&mut self, output: &mut String, start: Option<usize>, tight: bool, items: &[Vec<Block>]) {
+        match start {
+            None => output.push_str("<ul>"),
+            Some(1) => output.push_str("<ol>"),
+            Some(number) => output.push_str(&format!("<ol start=\"{}\">", number)),
+        }
+        self.block_end(output);
+        for item in items.iter() {
+            self.item(output, tight, item);
+        }
+        match start {
+            None => output.push_str("</ul>"),
+            Some(_) => output.push_str("</ol>"),
+        }
+        self.block_end(output);
+    }
+
+    fn item(&mut self, output: &mut String, tight: bool, children: &[Block]) {
+        output.push_str("<li>");
+        for (i, child) in children.iter().enumerate() {
+            match *child {
+                Block::Paragraph(ref content) if tight => {
+                    self.inlines(output, content);
+                    if i + 1 < children.len() {
+                        self.block_end(output);
+                    }
+                },
+                _ => {
+                    if i == 0 {
+                        self.block_end(output);
+                    }
+                    self.block(output, child);
+                },
+            }
+        }
+        output.push_str("</li>");
+        self.block_end(output);
+    }
+
+    fn html_block(&mut self, output: &mut String, literal: &str) {
+
+        // Escaped HTML is shown as a paragraph of text.
+        if self.options().safe {
+            output.push_str("<p>");
+            escape_html(output, literal);
+            output.push_str("</p>");
+        } else {
+            output.push_str(literal);
+        }
+        self.block_end(output);
+    }
+
+    fn thematic_break(&mut self, output: &mut String) {
+        self.void_tag(output, "hr");
+        self.block_end(output);
+    }
+
+    // INLINES //
+
+    fn text(&mut self, output: &mut String, text: &str) {
+        escape_html(output, text);
+    }
+
+    fn code(&mut self, output: &mut String, code: &str) {
+        output.push_str("<code>");
+        escape_html(output, code);
+        output.push_str("</code>");
+    }
+
+    fn emphasis(&mut self, output: &mut String, content: &[Inline]) {
+        output.push_str("<em>");
+        self.inlines(output, content);
+        output.push_str("</em>");
+    }
+
+    fn strong(&mut self, output: &mut String, content: &[Inline]) {
+        output.push_str("<strong>");
+        self.inlines(output, content);
+        output.push_str("</strong>");
+    }
+
+    fn link(&mut self, output: &mut String, destination: &str, title: Option<&str>, content: &[Inline]) {
+        output.push_str("<a href=\"");
+        self.url(output, destination);
+        output.push('"');
+        if let Some(title) = title {
+            output.push_str(" title=\"");
+            escape_html(output, title);
+            output.push('"');
+        }
+        output.push('>');
+        self.inlines(output, content);
+        output.push_str("</a>");
+    }
+
+    fn image(&mut self, output: &mut String, source: &str, title: Option<&str>, content: &[Inline]) {
+        output.push_str("<img src=\"");
+        self.url(output, source);
+        output.push_str("\" alt=\"");
+        self.plain_text(output, content);
+        output.push('"');
+        if let Some(title) = title {
+            output.push_str(" title=\"");
+            escape_html(output, title);
+            output.push('"');
+        }
+        self.close_void_tag(output);
+    }
+
+    fn html(&mut self, output: &mut String, html: &str) {
+        if self.options().safe {
+            escape_html(output, html);
+        } else {
+            output.push_str(html);
+        }
+    }
+
+    fn soft_break(&mut self, output: &mut String) {
+        match self.options().soft_break {
+            SoftBreak::Newline => output.push('\n'),
+            SoftBreak::Space => output.push(' '),
+            SoftBreak::Break => self.hard_break(output),
+        }
+    }
+
+    fn hard_break(&mut self, output: &mut String) {
+        self.void_tag(output, "br");
+        output.push('\n');
+    }
+
+    // HELPER FUNCTIONS //
+
+    /// Write inline content without any markup, as in the alt text of an
+    /// image.
+    fn plain_text(&self, output: &mut String, inlines: &[Inline]) {
+        for inline in inlines.iter() {
+            match *inline {
+                Inline::Text(ref text) | Inline::Code(ref text) | Inline::Html(ref text) => {
+                    escape_html(output, text);
+                },
+                Inline::Emphasis(ref content)
+                | Inline::Strong(ref content)
+                | Inline::Link { ref content, .. }
+                | Inline::Image { ref content, .. } => self.plain_text(output, content),
+                Inline::SoftBreak | Inline::HardBreak => output.push('\n'),
+            }
+        }
+    }
+
+    /// Write a link destination or image source. Safe mode leaves out URLs
+    /// that can run scripts or read local files.
+    fn url(&self, output: &mut String, url: &str) {
+        if self.options().safe && is_dangerous_url(url) {
+            return;
+        }
+        escape_href(output, url);
+    }
+
+    /// Write an element that has no content, such as `<hr />`.
+    fn void_tag(&self, output: &mut String, name: &str) {
+        output.push('<');
+        output.push_str(name);
+        self.close_void_tag(output);
+    }
+
+    fn close_void_tag(&self, output: &mut String) {
+        match self.options().xhtml {
+            true => output.push_str(" />"),
+            false => output.push('>'),
+        }
+    }
+
+    /// Separate blocks when pretty printing.
+    fn block_end(&self, output: &mut String) {
+        if self.options().pretty {
+            output.push('\n');
+        }
+    }
+}
+
+/// Check for `javascript:`, `vbscript:`, `file:` and `data:` URLs. Data URLs
+/// of common image formats are allowed.
+fn is_dangerous_url(url: &str) -> bool {
+
+    // Browsers ignore whitespace and control characters in the scheme.
+    let url: String = url.chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    if url.starts_with("data:") {
+        let images = ["data:image/png", "data:image/gif", "data:image/jpeg", "data:image/webp"];
+        return !images.iter().any(|image| url.starts_with(image));
+    }
+    return ["javascript:", "vbscript:", "file:"].iter().any(|scheme| url.starts_with(scheme));
+}
+
+/// The default renderer, which writes HTML.
+pub struct HtmlRenderer {
+    options: Options,
+}
+
+impl HtmlRenderer {
+    pub fn new() -> HtmlRenderer {
+        return HtmlRenderer::with_options(Options::new());
+    }
+
+    pub fn with_options(options: Options) -> HtmlRenderer {
+        return HtmlRenderer { options: options };
+    }
+}
+
+impl Default for HtmlRenderer {
+    fn default() -> HtmlRenderer {
+        return HtmlRenderer::new();
+    }
+}
+
+impl Renderer for HtmlRenderer {
+    fn options(&self) -> &Options {
+        return &self.options;
+    }
+}
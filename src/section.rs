@@ -5,12 +5,19 @@
 pub struct Section {
     pub start: usize,
     pub end: usize,
+    /// Spaces that come before the characters, left over from a tab that
+    /// was partly used as indentation.
+    pub padding: usize,
 }
 
 #[allow(dead_code)]
 impl Section {
     pub fn new(start: usize, end: usize) -> Section {
-        return Section { start: start, end: end };
+        return Section { start: start, end: end, padding: 0 };
+    }
+
+    pub fn padded(padding: usize, start: usize, end: usize) -> Section {
+        return Section { start: start, end: end, padding: padding };
     }
 
     pub fn slice_to_index(sections: &[Section]) -> Vec<usize> {
@@ -23,7 +30,7 @@ impl Section {
         return indexes;
     }
 
-    pub fn to_index(sections: &Vec<Section>) -> Vec<usize> {
+    pub fn to_index(sections: &[Section]) -> Vec<usize> {
         let mut indexes: Vec<usize> = Vec::new();
         for section in sections.iter() {
             for i in section.start..section.end + 1 {
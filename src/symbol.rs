@@ -9,6 +9,7 @@ pub enum Symbol {
     Underscore, // _
     Hyphen,
     Plus, // +
+    Tilde, // ~
     Newline, // \n
     NumberSign, // #
     Code, // `
@@ -38,6 +39,7 @@ impl Symbol {
             '_' => Symbol::Underscore,
             '-' => Symbol::Hyphen,
             '+' => Symbol::Plus,
+            '~' => Symbol::Tilde,
             '\r' | '\n' => Symbol::Newline,
             '\t' => Symbol::Tab,
             '#' => Symbol::NumberSign,
@@ -64,21 +66,24 @@ impl Symbol {
         symbols.push(Symbol::EndInput);
         return symbols;
     }
+    /// Characters that have to be written as entities in HTML.
     pub fn is_char_entity(&self) -> bool {
-        match self {
-            &Symbol::Ampersand => return true,
-            &Symbol::LeftBracket => return true,
-            &Symbol::RightBracket => return true,
-            &Symbol::LessThan => return true,
-            &Symbol::GreaterThan => return true,
-            _ => return false,
+        return self.char_entity().is_some();
+    }
+    pub fn char_entity(&self) -> Option<&'static str> {
+        match *self {
+            Symbol::Ampersand => return Some("&amp;"),
+            Symbol::LessThan => return Some("&lt;"),
+            Symbol::GreaterThan => return Some("&gt;"),
+            Symbol::Quote => return Some("&quot;"),
+            _ => return None,
         }
     }
     pub fn is_blank(&self) -> bool {
-        match self {
-            &Symbol::Space => return true,
-            &Symbol::Tab => return true,
-            &Symbol::Newline => return true,
+        match *self {
+            Symbol::Space => return true,
+            Symbol::Tab => return true,
+            Symbol::Newline => return true,
             _ => return false,
         }
     }
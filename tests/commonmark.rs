@@ -5,78 +5,756 @@ extern crate johnmark;
 use johnmark::convert;
 
 #[test]
-#[ignore]
+fn line_endings() {
+    // http://spec.commonmark.org/0.19/#line-ending
+    assert_eq!("<ul><li>a</li><li>b</li></ul>", convert("- a\r\n- b"));
+    assert_eq!("<p>foo<br />\nbar</p>", convert("foo  \r\nbar"));
+    assert_eq!("<pre><code>code\n</code></pre>", convert("```\r\ncode\r\n```\r\n"));
+    assert_eq!("<p>foo\nbar</p><p>baz</p>", convert("foo\rbar\r\rbaz"));
+}
+
+#[test]
 fn tab_expansion() {
-    assert_eq!("<pre><code>foo baz     bim</code></pre>", convert("\tfoo\tbaz\t\tbim"));
+    // Tabs are only expanded in indentation, as in later versions of the
+    // spec. http://spec.commonmark.org/0.31.2/#tabs
+    assert_eq!("<pre><code>foo\tbaz\t\tbim</code></pre>", convert("\tfoo\tbaz\t\tbim"));
+    assert_eq!("<pre><code>foo\tbaz\t\tbim</code></pre>", convert("  \tfoo\tbaz\t\tbim"));
+    assert_eq!("<pre><code>a\ta\nὐ\ta</code></pre>", convert("    a\ta\n    ὐ\ta"));
+    assert_eq!("<pre><code>foo\nbar</code></pre>", convert("    foo\n\tbar"));
+    assert_eq!("<h1>Foo</h1>", convert("#\tFoo"));
+    assert_eq!("<hr />", convert("*\t*\t*\t"));
+
+    // Indentation can use part of a tab, and the rest of it becomes spaces.
+    assert_eq!("<ul><li><p>foo</p><p>bar</p></li></ul>", convert("  - foo\n\n\tbar"));
+    assert_eq!("<ul><li><p>foo</p><pre><code>  bar</code></pre></li></ul>", convert("- foo\n\n\t\tbar"));
+    assert_eq!("<blockquote><pre><code>  foo</code></pre></blockquote>", convert(">\t\tfoo"));
+    assert_eq!("<ul><li>foo</li></ul>", convert("-\tfoo"));
+    assert_eq!("<ul><li>foo<ul><li>bar<ul><li>baz</li></ul></li></ul></li></ul>", convert(" - foo\n   - bar\n\t - baz"));
+
+    // Tabs anywhere else are kept.
+    assert_eq!("<pre>\n\tfoo\tbar\n</pre>", convert("<pre>\n\tfoo\tbar\n</pre>"));
+    assert_eq!("<pre><code>\tx\n</code></pre>", convert("```\n\tx\n```"));
+    assert_eq!("<p><code>a\tb</code> c\td</p>", convert("`a\tb` c\td"));
+}
+
+#[test]
+fn atx_headings() {
+    // http://spec.commonmark.org/0.19/#example-37
+    assert_eq!("<h1>foo <em>bar</em> *baz*</h1>", convert("# foo *bar* \\*baz\\*"));
+
+    // http://spec.commonmark.org/0.19/#example-38
+    assert_eq!("<h1>foo</h1>", convert("#                  foo                     "));
+
+    // http://spec.commonmark.org/0.19/#example-42
+    assert_eq!("<h2>foo</h2><h3>bar</h3>", convert("## foo ##\n  ###   bar    ###"));
+
+    // http://spec.commonmark.org/0.19/#example-43
+    assert_eq!("<h1>foo</h1><h5>foo</h5>", convert("# foo ##################################\n##### foo ##"));
+
+    // http://spec.commonmark.org/0.19/#example-44
+    assert_eq!("<h3>foo</h3>", convert("### foo ###     "));
+
+    // http://spec.commonmark.org/0.19/#example-45
+    assert_eq!("<h3>foo ### b</h3>", convert("### foo ### b"));
+
+    // http://spec.commonmark.org/0.19/#example-46
+    assert_eq!("<h1>foo#</h1>", convert("# foo#"));
+
+    // http://spec.commonmark.org/0.19/#example-47
+    assert_eq!("<h3>foo ###</h3><h2>foo ###</h2><h1>foo #</h1>", convert("### foo \\###\n## foo #\\##\n# foo \\#"));
+
+    // http://spec.commonmark.org/0.19/#example-50
+    assert_eq!("<h2></h2><h1></h1><h3></h3>", convert("## \n#\n### ###"));
+}
+
+#[test]
+fn setext_headings() {
+    // http://spec.commonmark.org/0.19/#example-51
+    assert_eq!("<h1>Foo <em>bar</em></h1><h2>Foo <em>bar</em></h2>", convert("Foo *bar*\n=========\n\nFoo *bar*\n---------"));
+
+    // Only spaces and tabs are trimmed from the end of the content.
+    assert_eq!("<h2>Foo\u{a0}</h2>", convert("Foo\u{a0} \n---"));
+
+    // The content of a heading can span several lines.
+    assert_eq!("<h1>Foo <em>bar\nbaz</em></h1>", convert("Foo *bar\nbaz*\n===="));
+    assert_eq!("<h2>Foo\nBar</h2>", convert("Foo\nBar\n---"));
+
+    // http://spec.commonmark.org/0.19/#example-53
+    assert_eq!("<h2>Foo</h2><h2>Foo</h2><h1>Foo</h1>", convert("   Foo\n---\n\n  Foo\n-----\n\n  Foo\n  ==="));
+
+    // http://spec.commonmark.org/0.19/#example-55
+    assert_eq!("<h2>Foo</h2>", convert("Foo\n   ----      "));
+
+    // http://spec.commonmark.org/0.19/#example-56
+    assert_eq!("<p>Foo\n---</p>", convert("Foo\n    ---"));
+
+    // http://spec.commonmark.org/0.19/#example-57
+    assert_eq!("<p>Foo\n= =</p><p>Foo</p><hr />", convert("Foo\n= =\n\nFoo\n--- -"));
+
+    // http://spec.commonmark.org/0.19/#example-62
+    assert_eq!("<blockquote><p>Foo</p></blockquote><hr />", convert("> Foo\n---"));
+
+    // http://spec.commonmark.org/0.19/#example-63
+    assert_eq!("<ul><li>Foo</li></ul><hr />", convert("- Foo\n---"));
+
+    // http://spec.commonmark.org/0.19/#example-65
+    assert_eq!(
+        "<hr /><h2>Foo</h2><h2>Bar</h2><p>Baz</p>",
+        convert("---\nFoo\n---\nBar\n---\nBaz")
+    );
+
+    // http://spec.commonmark.org/0.19/#example-66
+    assert_eq!("<p>====</p>", convert("\n===="));
+
+    // An underline cannot be a lazy continuation line.
+    assert_eq!("<blockquote><p>foo\nbar\n===</p></blockquote>", convert("> foo\nbar\n==="));
+
+    // Definitions are not part of the heading.
+    assert_eq!("<h1>bar</h1><p><a href=\"/url\">foo</a></p>", convert("[foo]: /url\nbar\n===\n\n[foo]"));
 }
 
 #[test]
-// TODO: Fix compatibility.
 fn blank_lines() {
-    assert_eq!("<p>aaa\n</p><h1>aaa</h1>", convert("  \n\naaa\n  \n\n# aaa\n\n  "));
+    assert_eq!("<p>aaa</p><h1>aaa</h1>", convert("  \n\naaa\n  \n\n# aaa\n\n  "));
+
+    // Only spaces and tabs are trimmed from the end of a paragraph.
+    assert_eq!("<p>aaa\u{a0}</p>", convert("aaa\u{a0}\t \n"));
 }
 
 #[test]
-#[ignore]
-// TODO: Write tests.
-fn container_blocks() {}
+fn fenced_code_blocks() {
+    // http://spec.commonmark.org/0.19/#fenced-code-blocks
+    assert_eq!("<pre><code>&lt;\n &gt;\n</code></pre>", convert("```\n<\n >\n```"));
+    assert_eq!("<pre><code>&lt;\n &gt;\n</code></pre>", convert("~~~\n<\n >\n~~~"));
+
+    // The closing fence has to use the same character and be at least as long.
+    assert_eq!("<pre><code>aaa\n~~~\n</code></pre>", convert("```\naaa\n~~~\n```"));
+    assert_eq!("<pre><code>aaa\n```\n</code></pre>", convert("````\naaa\n```\n``````"));
+    assert_eq!("<pre><code>aaa\n~~~ ~~</code></pre>", convert("~~~~~~\naaa\n~~~ ~~"));
+
+    // Unclosed code runs to the end of the document or its container.
+    assert_eq!("<pre><code>\n```\naaa</code></pre>", convert("`````\n\n```\naaa"));
+    assert_eq!(
+        "<blockquote><pre><code>aaa\n</code></pre></blockquote><p>bbb</p>",
+        convert("> ```\n> aaa\n\nbbb")
+    );
+    assert_eq!("<pre><code></code></pre>", convert("```\n```"));
+
+    // Indentation of the opening fence is removed from the content.
+    assert_eq!("<pre><code>aaa\naaa\n</code></pre>", convert(" ```\n aaa\naaa\n```"));
+    assert_eq!("<pre><code>aaa\n aaa\naaa\n</code></pre>", convert("   ```\n   aaa\n    aaa\n  aaa\n   ```"));
+    assert_eq!("<pre><code>```\naaa\n```</code></pre>", convert("    ```\n    aaa\n    ```"));
+    assert_eq!("<pre><code>aaa\n</code></pre>", convert("```\naaa\n  ```"));
+
+    // Fences can interrupt paragraphs.
+    assert_eq!("<p>foo</p><pre><code>bar\n</code></pre><p>baz</p>", convert("foo\n```\nbar\n```\nbaz"));
+
+    // The first word of the info string names the language.
+    assert_eq!(
+        "<pre><code class=\"language-ruby\">def foo(x)\n  return 3\nend\n</code></pre>",
+        convert("```ruby\ndef foo(x)\n  return 3\nend\n```")
+    );
+    assert_eq!(
+        "<pre><code class=\"language-ruby\">def foo(x)\n</code></pre>",
+        convert("~~~~    ruby startline=3 $%@#$\ndef foo(x)\n~~~~~~~")
+    );
+    assert_eq!("<pre><code class=\"language-;\"></code></pre>", convert("````;\n````"));
+    assert_eq!("<p><code>aa</code>\nfoo</p>", convert("``` aa ```\nfoo"));
+    assert_eq!("<pre><code class=\"language-f+o\"></code></pre>", convert("```f\\+o\n```"));
+}
 
 #[test]
-#[ignore]
-// TODO: Write tests.
-fn lists() {}
+fn html_blocks() {
+    // http://spec.commonmark.org/0.19/#example-104
+    assert_eq!("<div>\n  *hello*\n         <foo><a>", convert("<div>\n  *hello*\n         <foo><a>"));
+
+    // http://spec.commonmark.org/0.19/#example-105
+    assert_eq!("</div>\n*foo*", convert("</div>\n*foo*"));
+
+    // http://spec.commonmark.org/0.19/#example-106
+    assert_eq!("<DIV CLASS=\"foo\"><p><em>Markdown</em></p></DIV>", convert("<DIV CLASS=\"foo\">\n\n*Markdown*\n\n</DIV>"));
+
+    // http://spec.commonmark.org/0.19/#example-107
+    assert_eq!("<div id=\"foo\"\n  class=\"bar\">\n</div>", convert("<div id=\"foo\"\n  class=\"bar\">\n</div>"));
+
+    // Any complete tag alone on a line starts a block, but cannot
+    // interrupt a paragraph.
+    assert_eq!("<a href=\"foo\">\n*bar*\n</a>", convert("<a href=\"foo\">\n*bar*\n</a>"));
+    assert_eq!("<Warning>\n*bar*\n</Warning>", convert("<Warning>\n*bar*\n</Warning>"));
+    assert_eq!("<p>Foo</p><div>\nbar\n</div>", convert("Foo\n<div>\nbar\n</div>"));
+
+    // Raw tags end at their closing tag, even across blank lines.
+    assert_eq!(
+        "<pre language=\"haskell\"><code>\nimport Text.HTML.TagSoup\n\nmain :: IO ()\n</code></pre><p>okay</p>",
+        convert("<pre language=\"haskell\"><code>\nimport Text.HTML.TagSoup\n\nmain :: IO ()\n</code></pre>\nokay")
+    );
+    assert_eq!("<style\n  type=\"text/css\">\n\nfoo", convert("<style\n  type=\"text/css\">\n\nfoo"));
+
+    // Comments, processing instructions, declarations and CDATA.
+    assert_eq!("<!-- foo -->*bar*<p><em>baz</em></p>", convert("<!-- foo -->*bar*\n*baz*"));
+    assert_eq!("<!-- Foo\n\nbar\n   baz --><p>okay</p>", convert("<!-- Foo\n\nbar\n   baz -->\nokay"));
+    assert_eq!("<?php\n\n  echo \">\";\n\n?><p>okay</p>", convert("<?php\n\n  echo \">\";\n\n?>\nokay"));
+    assert_eq!("<!DOCTYPE html>", convert("<!DOCTYPE html>"));
+    assert_eq!("<![CDATA[\nfoo\n\n]]><p>okay</p>", convert("<![CDATA[\nfoo\n\n]]>\nokay"));
+
+    // Indentation.
+    assert_eq!("  <!-- foo --><pre><code>&lt;!-- foo --&gt;</code></pre>", convert("  <!-- foo -->\n\n    <!-- foo -->"));
+
+    // Inside containers.
+    assert_eq!("<blockquote><div>\nfoo</blockquote><p>bar</p>", convert("> <div>\n> foo\n\nbar"));
+    assert_eq!("<ul><li><div></li><li>foo</li></ul>", convert("- <div>\n- foo"));
+}
+
+#[test]
+fn container_blocks() {
+    // http://spec.commonmark.org/0.19/#block-quotes
+    assert_eq!("<blockquote><h1>Foo</h1><p>bar\nbaz</p></blockquote>", convert("> # Foo\n> bar\n> baz"));
+    assert_eq!(
+        "<blockquote><pre><code>foo\n</code></pre></blockquote><pre><code>bar</code></pre>",
+        convert(">     foo\n    bar")
+    );
+    assert_eq!("<blockquote><p>foo</p></blockquote><blockquote><p>bar</p></blockquote>", convert("> foo\n\n> bar"));
+    assert_eq!(
+        "<blockquote><p>aaa</p></blockquote><hr /><blockquote><p>bbb</p></blockquote>",
+        convert("> aaa\n***\n> bbb")
+    );
+
+    // Lazy continuation lines only continue paragraphs.
+    assert_eq!("<blockquote><p>foo\nbar</p></blockquote>", convert("> foo\nbar"));
+    assert_eq!("<blockquote><p>foo</p></blockquote><hr />", convert("> foo\n---"));
+    assert_eq!(
+        "<blockquote><ul><li>foo</li></ul></blockquote><ul><li>bar</li></ul>",
+        convert("> - foo\n- bar")
+    );
+    assert_eq!(
+        "<blockquote><blockquote><blockquote><p>foo\nbar\nbaz</p></blockquote></blockquote></blockquote>",
+        convert(">>> foo\n> bar\n>>baz")
+    );
+
+    // Markers nested too deeply are left as text.
+    let html = convert(&">".repeat(50000));
+    assert_eq!(100, html.matches("<blockquote>").count());
+    assert!(html.contains("<p>&gt;&gt;&gt;"));
+    assert_eq!(50, convert(&("- ".repeat(50000) + "a")).matches("<ul>").count());
+
+    // http://spec.commonmark.org/0.19/#list-items
+    assert_eq!(
+        "<ol><li><p>A paragraph\nwith two lines.</p><pre><code>indented code\n</code></pre>\
+         <blockquote><p>A block quote.</p></blockquote></li></ol>",
+        convert("1.  A paragraph\n    with two lines.\n\n        indented code\n\n    > A block quote.")
+    );
+    assert_eq!("<ol><li><p>a</p><p>b</p></li></ol><p>c</p>", convert("1. a\n\n   b\n\n  c"));
+    assert_eq!("<ul><li><p>foo</p><p>bar</p></li></ul>", convert("  - foo\n\n    bar"));
+    assert_eq!("<ul><li><p>a</p></li><li></li><li><p>c</p></li></ul>", convert("* a\n*\n\n* c"));
+}
+
+#[test]
+fn lists() {
+    // http://spec.commonmark.org/0.19/#lists
+    // Changing the bullet starts a new list.
+    assert_eq!(
+        "<ul><li>foo</li><li>bar</li></ul><ul><li>baz</li></ul>",
+        convert("- foo\n- bar\n+ baz")
+    );
+
+    // Lists can interrupt a paragraph.
+    assert_eq!("<p>Foo</p><ul><li>bar</li><li>baz</li></ul>", convert("Foo\n- bar\n- baz"));
+
+    // Thematic breaks take precedence over list items.
+    assert_eq!("<hr />", convert("* * *"));
+    assert_eq!("<ul><li>foo</li></ul><hr /><ul><li>bar</li></ul>", convert("- foo\n***\n- bar"));
+
+    // Blank lines between items or blocks make a list loose.
+    assert_eq!("<ul><li><p>foo</p></li><li><p>bar</p></li></ul>", convert("- foo\n\n- bar"));
+    assert_eq!("<ul><li><p>foo</p><p>bar</p></li></ul>", convert("- foo\n\n  bar"));
+    assert_eq!(
+        "<ul><li>a<ul><li><p>b</p><p>c</p></li></ul></li><li>d</li></ul>",
+        convert("- a\n  - b\n\n    c\n- d")
+    );
+
+    // Items can contain other blocks.
+    assert_eq!(
+        "<ul><li><h1>Foo</h1></li><li><h2>Bar</h2>baz</li></ul>",
+        convert("- # Foo\n- Bar\n  ---\n  baz")
+    );
+    assert_eq!("<blockquote><ul><li>a</li><li>b</li></ul></blockquote>", convert("> - a\n> - b"));
+
+    // Continuation lines and items starting with a blank line.
+    assert_eq!("<ul><li>a\nb</li></ul>", convert("- a\nb"));
+    assert_eq!("<ul><li>foo</li></ul>", convert("-\n  foo"));
+    assert_eq!("<ul><li></li></ul><p>foo</p>", convert("-\n\n  foo"));
+    assert_eq!(
+        "<ul><li>a</li><li>b</li><li>c</li><li>d\n- e</li></ul>",
+        convert("- a\n - b\n  - c\n   - d\n    - e")
+    );
+
+    // Ordered lists keep the number of their first item.
+    assert_eq!("<ol><li>a</li><li>b</li></ol>", convert("1. a\n2. b"));
+    assert_eq!("<ol start=\"3\"><li>a</li><li>b</li></ol>", convert("3) a\n4) b"));
+    assert_eq!("<ol start=\"0\"><li>ok</li></ol>", convert("0. ok"));
+    assert_eq!("<p>1234567890. not ok</p>", convert("1234567890. not ok"));
+
+    // Changing the delimiter or the kind of list starts a new list.
+    assert_eq!("<ol><li>a</li></ol><ol start=\"2\"><li>b</li></ol>", convert("1. a\n2) b"));
+    assert_eq!("<ul><li>a</li></ul><ol><li>b</li></ol>", convert("- a\n1. b"));
+
+    // Only lists starting at 1 can interrupt a paragraph.
+    assert_eq!("<p>Foo</p><ol><li>bar</li></ol>", convert("Foo\n1. bar"));
+    assert_eq!(
+        "<p>The number of windows in my house is\n14.  The number of doors is 6.</p>",
+        convert("The number of windows in my house is\n14.  The number of doors is 6.")
+    );
+
+    assert_eq!(
+        "<ol start=\"10\"><li>foo<ul><li>bar</li></ul></li></ol>",
+        convert("10) foo\n    - bar")
+    );
+}
 
 #[test]
 // TODO: Write tests.
 fn inlines() {
     // http://spec.commonmark.org/0.19/#example-227
     assert_eq!("<p><code>hi</code>lo`</p>", convert("`hi`lo`"));
+
+    // Only a run of the same length closes a code span.
+    assert_eq!("<p>`foo<code>bar</code></p>", convert("`foo``bar``"));
+
+    // One space is stripped from each end of a code span.
+    assert_eq!("<p><code>``</code></p>", convert("` `` `"));
+    assert_eq!("<p><code> a</code></p>", convert("`  a `"));
+    assert_eq!("<p><code>  </code></p>", convert("`  `"));
+    assert_eq!("<p><code>foo bar baz</code></p>", convert("`foo\nbar baz`"));
 }
 
 #[test]
-#[ignore]
-// TODO: Write tests.
-fn entities() {}
+fn backslash_escapes() {
+    // http://spec.commonmark.org/0.19/#backslash-escapes
+    assert_eq!(
+        "<p>!&quot;#$%&amp;'()*+,-./:;&lt;=&gt;?@[\\]^_`{|}~</p>",
+        convert("\\!\\\"\\#\\$\\%\\&\\'\\(\\)\\*\\+\\,\\-\\.\\/\\:\\;\\<\\=\\>\\?\\@\\[\\\\\\]\\^\\_\\`\\{\\|\\}\\~")
+    );
+    assert_eq!("<p>\\→\\A\\a\\ \\3\\φ\\«</p>", convert("\\→\\A\\a\\ \\3\\φ\\«"));
+    assert_eq!(
+        "<p>*not emphasized*\n[not a link](/foo)\n`not code`\n# not a header\n&amp;copy;</p>",
+        convert("\\*not emphasized*\n\\[not a link](/foo)\n\\`not code`\n\\# not a header\n\\&copy;")
+    );
+    assert_eq!("<p>&gt; not a quote</p>", convert("\\> not a quote"));
+    assert_eq!("<p>\\<em>emphasis</em></p>", convert("\\\\*emphasis*"));
+    assert_eq!("<p>*foo*</p>", convert("*foo\\*"));
+    assert_eq!("<p>foo<br />\nbar</p>", convert("foo\\\nbar"));
+
+    // Escapes do not work in code.
+    assert_eq!("<p><code>\\[\\]</code></p>", convert("`\\[\\]`"));
+    assert_eq!("<pre><code>\\[\\]</code></pre>", convert("    \\[\\]"));
+    assert_eq!(
+        "<p><a href=\"http://example.com?find=%5C*\">http://example.com?find=\\*</a></p>",
+        convert("<http://example.com?find=\\*>")
+    );
+
+    assert_eq!("<p><a href=\"/x\">foo]</a></p>", convert("[foo\\]](/x)"));
+    assert_eq!(
+        "<p><a href=\"/bar*\" title=\"ti*tle\">foo</a></p>",
+        convert("[foo](/bar\\* \"ti\\*tle\")")
+    );
+    assert_eq!("<p><a href=\"foo):\">link</a></p>", convert("[link](foo\\)\\:)"));
+}
 
 #[test]
-#[ignore]
-// TODO: Write tests.
-fn emphasis_and_strong_emphasis() {}
+fn entities() {
+    // http://spec.commonmark.org/0.19/#example-262
+    assert_eq!(
+        "<p>\u{a0} &amp; © Æ Ď ¾ ℋ ⅆ ∲ ≧\u{338}</p>",
+        convert("&nbsp; &amp; &copy; &AElig; &Dcaron; &frac34; &HilbertSpace; &DifferentialD; \
+                 &ClockwiseContourIntegral; &ngE;")
+    );
+
+    // http://spec.commonmark.org/0.19/#example-263
+    assert_eq!("<p># Ӓ Ϡ � �</p>", convert("&#35; &#1234; &#992; &#98765432; &#0;"));
+
+    // http://spec.commonmark.org/0.19/#example-264
+    assert_eq!("<p>&quot; ആ ಫ</p>", convert("&#X22; &#XD06; &#xcab;"));
+
+    // http://spec.commonmark.org/0.19/#example-265
+    assert_eq!(
+        "<p>&amp;nbsp &amp;x; &amp;#; &amp;#x; &amp;ThisIsWayTooLongToBeAnEntityIsntIt; &amp;hi?;</p>",
+        convert("&nbsp &x; &#; &#x; &ThisIsWayTooLongToBeAnEntityIsntIt; &hi?;")
+    );
+
+    // http://spec.commonmark.org/0.19/#example-266
+    assert_eq!("<p>&amp;copy</p>", convert("&copy"));
+
+    // http://spec.commonmark.org/0.19/#example-267
+    assert_eq!("<p>&amp;MadeUpEntity;</p>", convert("&MadeUpEntity;"));
+
+    // http://spec.commonmark.org/0.19/#example-270
+    assert_eq!(
+        "<p><a href=\"/f%C3%B6%C3%B6\" title=\"föö\">foo</a></p>",
+        convert("[foo](/f&ouml;&ouml; \"f&ouml;&ouml;\")")
+    );
+
+    // http://spec.commonmark.org/0.19/#example-271
+    assert_eq!(
+        "<p><a href=\"/f%C3%B6%C3%B6\" title=\"föö\">foo</a></p>",
+        convert("[foo]: /f&ouml;&ouml; \"f&ouml;&ouml;\"\n\n[foo]")
+    );
+
+    // http://spec.commonmark.org/0.19/#example-273
+    assert_eq!("<p><code>f&amp;ouml;&amp;ouml;</code></p>", convert("`f&ouml;&ouml;`"));
+
+    // http://spec.commonmark.org/0.19/#example-274
+    assert_eq!("<pre><code>f&amp;ouml;f&amp;ouml;</code></pre>", convert("    f&ouml;f&ouml;"));
+}
 
 #[test]
-#[ignore]
-// TODO: Write tests.
-fn links() {}
+fn emphasis_and_strong_emphasis() {
+    // http://spec.commonmark.org/0.19/#example-328
+    assert_eq!("<p><em>foo bar</em></p>", convert("*foo bar*"));
+
+    // http://spec.commonmark.org/0.19/#example-329
+    assert_eq!("<p>a * foo bar*</p>", convert("a * foo bar*"));
+
+    // http://spec.commonmark.org/0.19/#example-332
+    assert_eq!("<p>foo<em>bar</em></p>", convert("foo*bar*"));
+
+    // http://spec.commonmark.org/0.19/#example-334
+    assert_eq!("<p><em>foo bar</em></p>", convert("_foo bar_"));
+
+    // http://spec.commonmark.org/0.19/#example-335
+    assert_eq!("<p>_ foo bar_</p>", convert("_ foo bar_"));
+
+    // http://spec.commonmark.org/0.19/#example-337
+    assert_eq!("<p>foo_bar_</p>", convert("foo_bar_"));
+
+    // http://spec.commonmark.org/0.19/#example-339
+    assert_eq!("<p>пристаням_стремятся_</p>", convert("пристаням_стремятся_"));
+
+    // http://spec.commonmark.org/0.19/#example-340
+    assert_eq!("<p>aa_&quot;bb&quot;_cc</p>", convert("aa_\"bb\"_cc"));
+
+    // http://spec.commonmark.org/0.19/#example-343
+    assert_eq!("<p><em>(<em>foo</em>)</em></p>", convert("*(*foo*)*"));
+
+    // http://spec.commonmark.org/0.19/#example-345
+    assert_eq!("<p><em>foo</em>bar</p>", convert("*foo*bar"));
+
+    // http://spec.commonmark.org/0.19/#example-350
+    assert_eq!("<p><em>(<em>foo</em>)</em></p>", convert("_(_foo_)_"));
+
+    // http://spec.commonmark.org/0.19/#example-352
+    assert_eq!("<p><em>foo_bar_baz</em></p>", convert("_foo_bar_baz_"));
+
+    // http://spec.commonmark.org/0.19/#example-353
+    assert_eq!("<p><strong>foo bar</strong></p>", convert("**foo bar**"));
+
+    // http://spec.commonmark.org/0.19/#example-357
+    assert_eq!("<p><strong>foo bar</strong></p>", convert("__foo bar__"));
+
+    // http://spec.commonmark.org/0.19/#example-361
+    assert_eq!("<p>foo__bar__</p>", convert("foo__bar__"));
+
+    // http://spec.commonmark.org/0.19/#example-367
+    assert_eq!("<p><strong>foo &quot;<em>bar</em>&quot; foo</strong></p>", convert("**foo \"*bar*\" foo**"));
+
+    // Nesting.
+    assert_eq!("<p><em>foo <strong>bar</strong> baz</em></p>", convert("*foo **bar** baz*"));
+    assert_eq!("<p><em>foo<strong>bar</strong>baz</em></p>", convert("*foo**bar**baz*"));
+    assert_eq!("<p><em><strong>foo</strong> bar</em></p>", convert("***foo** bar*"));
+    assert_eq!("<p><strong>foo, <strong>bar</strong>, baz</strong></p>", convert("__foo, __bar__, baz__"));
+    assert_eq!("<p><em><em>foo</em> bar</em></p>", convert("__foo_ bar_"));
+
+    // Delimiters nested too deeply are left as text.
+    let stars = "*".repeat(50000);
+    let html = convert(&format!("{}a{}", stars, stars));
+    assert_eq!(100, html.matches("<strong>").count());
+    assert!(html.starts_with("<p>****"));
+    let html = convert(&format!("{}[a](u){}", stars, stars));
+    assert_eq!(99, html.matches("<strong>").count());
+
+    // The rule of 3.
+    assert_eq!("<p><em>foo**bar</em></p>", convert("*foo**bar*"));
+    assert_eq!("<p>foo<em><strong>bar</strong></em>baz</p>", convert("foo***bar***baz"));
+
+    // Code spans bind more tightly than emphasis.
+    assert_eq!("<p>*a <code>*</code></p>", convert("*a `*`"));
+    assert_eq!("<p>*foo*</p>", convert("\\*foo*"));
+}
 
 #[test]
-#[ignore]
-// TODO: Write tests.
-fn images() {}
+fn links() {
+    // http://spec.commonmark.org/0.19/#example-459
+    assert_eq!("<p><a href=\"/uri\" title=\"title\">link</a></p>", convert("[link](/uri \"title\")"));
+
+    // http://spec.commonmark.org/0.19/#example-460
+    assert_eq!("<p><a href=\"/uri\">link</a></p>", convert("[link](/uri)"));
+
+    // http://spec.commonmark.org/0.19/#example-461
+    assert_eq!("<p><a href=\"\">link</a></p>", convert("[link]()"));
+
+    // http://spec.commonmark.org/0.19/#example-462
+    assert_eq!("<p><a href=\"\">link</a></p>", convert("[link](<>)"));
+
+    // http://spec.commonmark.org/0.19/#example-463
+    assert_eq!("<p>[link](/my uri)</p>", convert("[link](/my uri)"));
+
+    // http://spec.commonmark.org/0.19/#example-468
+    assert_eq!("<p><a href=\"foo(and(bar))\">link</a></p>", convert("[link](foo(and(bar)))"));
+
+    // An unbalanced parenthesis ends the destination.
+    assert_eq!("<p>[link](foo(and(bar))</p>", convert("[link](foo(and(bar))"));
+    assert_eq!("<p><a href=\"foo\">link</a>bar)</p>", convert("[link](foo)bar)"));
+
+    // Escaped parentheses do not have to be balanced.
+    assert_eq!("<p><a href=\"(foo)\">link</a></p>", convert("[link](\\(foo\\))"));
+    assert_eq!("<p><a href=\"foo(and(bar)\">link</a></p>", convert("[link](foo\\(and\\(bar\\))"));
+
+    // Titles may use any of the three delimiters.
+    assert_eq!("<p><a href=\"/url\" title=\"title\">link</a></p>", convert("[link](/url 'title')"));
+    assert_eq!("<p><a href=\"/url\" title=\"title\">link</a></p>", convert("[link](/url (title))"));
+    assert_eq!(
+        "<p><a href=\"/uri\" title=\"title\">link</a></p>",
+        convert("[link](   /uri\n  \"title\"  )")
+    );
+
+    // Brackets and inline content in the link text.
+    assert_eq!("<p><a href=\"/uri\">link [foo [bar]]</a></p>", convert("[link [foo [bar]]](/uri)"));
+    assert_eq!(
+        "<p><a href=\"/uri\">link <em>foo</em> <code>#</code></a></p>",
+        convert("[link *foo* `#`](/uri)")
+    );
+    assert_eq!("<p>[link] bar](/uri)</p>", convert("[link] bar](/uri)"));
+
+    // Links may not contain other links.
+    assert_eq!("<p>[foo <a href=\"/uri\">bar</a>](/uri)</p>", convert("[foo [bar](/uri)](/uri)"));
+
+    // Code spans bind more tightly than links.
+    assert_eq!("<p>[foo<code>](/uri)</code></p>", convert("[foo`](/uri)`"));
+}
 
 #[test]
-#[ignore]
-// TODO: Write tests.
-fn autolinks() {}
+fn link_reference_definitions() {
+    // http://spec.commonmark.org/0.19/#example-158
+    assert_eq!(
+        "<p><a href=\"/url\" title=\"title\">foo</a></p>",
+        convert("[foo]: /url \"title\"\n\n[foo]")
+    );
+
+    // http://spec.commonmark.org/0.19/#example-160
+    assert_eq!(
+        "<p><a href=\"my%20url\" title=\"title\">Foo bar</a></p>",
+        convert("[Foo bar]:\n<my url>\n'title'\n\n[Foo bar]")
+    );
+
+    // A title followed by other text is not part of the definition.
+    assert_eq!("<p>[foo]: /url &quot;title&quot; ok</p>", convert("[foo]: /url \"title\" ok"));
+    assert_eq!("<p>&quot;title&quot; ok</p>", convert("[foo]: /url\n\"title\" ok"));
+
+    // Several definitions in a row, used before and after.
+    assert_eq!(
+        "<p><a href=\"/u\">foo</a> <a href=\"/v\">bar</a></p>",
+        convert("[foo]: /u\n[bar]: /v\n[foo] [bar]")
+    );
+
+    // Definitions inside blockquotes apply to the whole document.
+    assert_eq!(
+        "<blockquote></blockquote><p><a href=\"/q\">foo</a></p>",
+        convert("> [foo]: /q\n\n[foo]")
+    );
+
+    // The first definition wins.
+    assert_eq!("<p><a href=\"/first\">foo</a></p>", convert("[foo]: /first\n[foo]: /second\n\n[foo]"));
+}
 
 #[test]
-#[ignore]
-// TODO: Write tests.
-fn raw_html() {}
+fn reference_links() {
+    // Full, collapsed and shortcut references.
+    assert_eq!(
+        "<p><a href=\"/url\" title=\"title\">link</a></p>",
+        convert("[foo]: /url \"title\"\n\n[link][foo]")
+    );
+    assert_eq!("<p><a href=\"/url\">foo</a></p>", convert("[foo]: /url\n\n[foo][]"));
+    assert_eq!("<p><a href=\"/url\"><em>foo</em> bar</a></p>", convert("[*foo* bar]: /url\n\n[*foo* bar]"));
+
+    // Labels are case insensitive and ignore extra whitespace.
+    assert_eq!(
+        "<p><a href=\"/url\">Foo</a> and <a href=\"/url\">bar</a></p>",
+        convert("[FOO]:  /url\n\n[Foo][] and [bar][ foo ]")
+    );
+    assert_eq!("<p><a href=\"/url\">ẞ</a></p>", convert("[SS]: /url\n\n[ẞ]"));
+
+    // Undefined labels are left alone.
+    assert_eq!("<p>[foo][bar]</p>", convert("[foo][bar]"));
+    assert_eq!("<p>[foo]</p>", convert("[foo]"));
+}
+
+#[test]
+fn images() {
+    // http://spec.commonmark.org/0.19/#images
+    assert_eq!("<p><img src=\"/url\" alt=\"foo\" title=\"title\" /></p>", convert("![foo](/url \"title\")"));
+    assert_eq!(
+        "<p><img src=\"train.jpg\" alt=\"foo bar\" title=\"train &amp; tracks\" /></p>",
+        convert("[foo *bar*]: train.jpg \"train & tracks\"\n\n![foo *bar*]")
+    );
+    assert_eq!("<p><img src=\"/url2\" alt=\"foo bar\" /></p>", convert("![foo ![bar](/url)](/url2)"));
+    assert_eq!("<p><img src=\"/url2\" alt=\"foo bar\" /></p>", convert("![foo [bar](/url)](/url2)"));
+    assert_eq!(
+        "<p>My <img src=\"/path/to/train.jpg\" alt=\"foo bar\" title=\"title\" /></p>",
+        convert("My ![foo bar](/path/to/train.jpg  \"title\"   )")
+    );
+    assert_eq!("<p><img src=\"/url\" alt=\"\" /></p>", convert("![](/url)"));
+
+    // Images inside of links.
+    assert_eq!(
+        "<p><a href=\"/uri\"><img src=\"moon.jpg\" alt=\"moon\" /></a></p>",
+        convert("[![moon](moon.jpg)](/uri)")
+    );
+    assert_eq!("<p>!foo ![foo]</p>", convert("!foo ![foo]"));
+
+    // Images nested too deeply are left as text.
+    let html = convert(&format!("{}a{}", "![".repeat(50000), "](u)".repeat(50000)));
+    assert_eq!(1, html.matches("<img").count());
+    assert!(html.starts_with("<p>![![![!["));
+    assert!(html.contains("\" />](u)](u)"));
+}
+
+#[test]
+fn autolinks() {
+    // http://spec.commonmark.org/0.19/#autolinks
+    assert_eq!("<p><a href=\"http://foo.bar.baz\">http://foo.bar.baz</a></p>", convert("<http://foo.bar.baz>"));
+    assert_eq!("<p><a href=\"irc://foo.bar:2233/baz\">irc://foo.bar:2233/baz</a></p>", convert("<irc://foo.bar:2233/baz>"));
+    assert_eq!("<p><a href=\"MAILTO:FOO@BAR.BAZ\">MAILTO:FOO@BAR.BAZ</a></p>", convert("<MAILTO:FOO@BAR.BAZ>"));
+
+    // Any scheme of two or more characters will do.
+    assert_eq!("<p><a href=\"a+b+c:d\">a+b+c:d</a></p>", convert("<a+b+c:d>"));
+    assert_eq!("<p><a href=\"localhost:5001/foo\">localhost:5001/foo</a></p>", convert("<localhost:5001/foo>"));
+
+    // Email addresses get a 'mailto:' destination.
+    assert_eq!(
+        "<p><a href=\"mailto:foo@bar.example.com\">foo@bar.example.com</a></p>",
+        convert("<foo@bar.example.com>")
+    );
+    assert_eq!(
+        "<p><a href=\"mailto:foo+special@Bar.baz-bar0.com\">foo+special@Bar.baz-bar0.com</a></p>",
+        convert("<foo+special@Bar.baz-bar0.com>")
+    );
+
+    // Not autolinks.
+    assert_eq!("<p>http://example.com</p>", convert("http://example.com"));
+    assert!(!convert("<>").contains("<a"));
+    assert!(!convert("<m:abc>").contains("<a"));
+    assert!(!convert("<foo.bar.baz>").contains("<a"));
+    assert!(!convert("<http://foo.bar/baz bim>").contains("<a"));
+    assert!(!convert("<foo@bar-.com>").contains("<a"));
+
+    // Autolinks take precedence over the brackets of links.
+    assert_eq!(
+        "<p>[foo<a href=\"http://example.com/?search=%5D(uri)\">http://example.com/?search=](uri)</a></p>",
+        convert("[foo<http://example.com/?search=](uri)>")
+    );
+}
+
+#[test]
+fn raw_html() {
+    // http://spec.commonmark.org/0.19/#example-497
+    assert_eq!("<p><a><bab><c2c></p>", convert("<a><bab><c2c>"));
+
+    // http://spec.commonmark.org/0.19/#example-498
+    assert_eq!("<p><a/><b2/></p>", convert("<a/><b2/>"));
+
+    // http://spec.commonmark.org/0.19/#example-499
+    assert_eq!("<p><a  /><b2\ndata=\"foo\" ></p>", convert("<a  /><b2\ndata=\"foo\" >"));
+
+    // http://spec.commonmark.org/0.19/#example-500
+    assert_eq!(
+        "<p><a foo=\"bar\" bam = 'baz <em>\"</em>'\n_boolean zoop:33=zoop:33 /></p>",
+        convert("<a foo=\"bar\" bam = 'baz <em>\"</em>'\n_boolean zoop:33=zoop:33 />")
+    );
+
+    // http://spec.commonmark.org/0.19/#example-501
+    assert_eq!("<p>&lt;33&gt; &lt;__&gt;</p>", convert("<33> <__>"));
+
+    // http://spec.commonmark.org/0.19/#example-502
+    assert_eq!("<p>&lt;a h*#ref=&quot;hi&quot;&gt;</p>", convert("<a h*#ref=\"hi\">"));
+
+    // http://spec.commonmark.org/0.19/#example-503
+    assert_eq!(
+        "<p>&lt;a href=&quot;hi'&gt; &lt;a href=hi'&gt;</p>",
+        convert("<a href=\"hi'> <a href=hi'>")
+    );
+
+    // http://spec.commonmark.org/0.19/#example-504
+    assert_eq!("<p>&lt; a&gt;&lt;\nfoo&gt;&lt;bar/ &gt;</p>", convert("< a><\nfoo><bar/ >"));
+
+    // http://spec.commonmark.org/0.19/#example-505
+    assert_eq!("<p>&lt;a href='bar'title=title&gt;</p>", convert("<a href='bar'title=title>"));
+
+    // http://spec.commonmark.org/0.19/#example-506
+    assert_eq!("<p></a></foo ></p>", convert("</a></foo >"));
+
+    // http://spec.commonmark.org/0.19/#example-507
+    assert_eq!("<p>&lt;/a href=&quot;foo&quot;&gt;</p>", convert("</a href=\"foo\">"));
+
+    // http://spec.commonmark.org/0.19/#example-508
+    assert_eq!(
+        "<p>foo <!-- this is a\ncomment - with hyphen --></p>",
+        convert("foo <!-- this is a\ncomment - with hyphen -->")
+    );
+
+    // http://spec.commonmark.org/0.19/#example-511
+    assert_eq!("<p>foo <?php echo $a; ?></p>", convert("foo <?php echo $a; ?>"));
+
+    // http://spec.commonmark.org/0.19/#example-512
+    assert_eq!("<p>foo <!ELEMENT br EMPTY></p>", convert("foo <!ELEMENT br EMPTY>"));
+
+    // http://spec.commonmark.org/0.19/#example-513
+    assert_eq!("<p>foo <![CDATA[>&<]]></p>", convert("foo <![CDATA[>&<]]>"));
+
+    // http://spec.commonmark.org/0.19/#example-514
+    assert_eq!("<p>foo <a href=\"&ouml;\"></p>", convert("foo <a href=\"&ouml;\">"));
+
+    // http://spec.commonmark.org/0.19/#example-515
+    assert_eq!("<p>foo <a href=\"\\*\"></p>", convert("foo <a href=\"\\*\">"));
+
+    // http://spec.commonmark.org/0.19/#example-516
+    assert_eq!("<p>&lt;a href=&quot;&quot;&quot;&gt;</p>", convert("<a href=\"\\\"\">"));
+
+    // Tags take precedence over emphasis and links.
+    assert_eq!("<p>Press <kbd>Ctrl</kbd></p>", convert("Press <kbd>Ctrl</kbd>"));
+    assert_eq!("<p><em><span class=\"x\"></em></span></p>", convert("*<span class=\"x\">*</span>"));
+    assert_eq!("<p>[foo <bar attr=\"](baz)\"></p>", convert("[foo <bar attr=\"](baz)\">"));
+
+    // http://spec.commonmark.org/0.19/#example-116
+    assert_eq!(
+        "<table><tr><td>\n<pre>\n**Hello**,<p><em>world</em>.\n</pre></p></td></tr></table>",
+        convert("<table><tr><td>\n<pre>\n**Hello**,\n\n_world_.\n</pre>\n</td></tr></table>")
+    );
+}
 
 #[test]
-#[ignore]
-// TODO: Finish adding tests and make compatible.
 fn hard_line_breaks() {
     // http://spec.commonmark.org/0.19/#example-529
     assert_eq!("<p>foo<br />\nbaz</p>", convert("foo  \nbaz"));
 
     // http://spec.commonmark.org/0.19/#example-530
     assert_eq!("<p>foo<br />\nbaz</p>", convert("foo       \nbaz"));
+
+    // http://spec.commonmark.org/0.19/#example-531
+    assert_eq!("<p>foo<br />\nbaz</p>", convert("foo\\\nbaz"));
+
+    // http://spec.commonmark.org/0.19/#example-532
+    assert_eq!("<p>foo<br />\nbar</p>", convert("foo  \n     bar"));
+
+    // http://spec.commonmark.org/0.19/#example-534
+    assert_eq!("<p><em>foo<br />\nbar</em></p>", convert("*foo  \nbar*"));
+
+    // http://spec.commonmark.org/0.19/#example-540
+    assert_eq!("<p>foo\\</p>", convert("foo\\"));
+
+    // http://spec.commonmark.org/0.19/#example-541
+    assert_eq!("<p>foo</p>", convert("foo  "));
 }
 
 #[test]
-#[ignore]
-// TODO
 fn soft_line_breaks() {
     // http://spec.commonmark.org/0.19/#example-544
     assert_eq!("<p>foo\nbaz</p>", convert("foo\nbaz"));
@@ -0,0 +1,71 @@
+extern crate johnmark;
+
+use johnmark::{parse, Block, Document, Inline};
+
+fn text(string: &str) -> Inline {
+    Inline::Text(string.to_string())
+}
+
+#[test]
+fn blocks() {
+    let document = parse("# Header\n\n---\n\n    code");
+    assert_eq!(
+        vec![
+            Block::Heading { level: 1, content: vec![text("Header")] },
+            Block::ThematicBreak,
+            Block::CodeBlock { info: String::new(), literal: "code".to_string() },
+        ],
+        document.blocks
+    );
+    assert_eq!(
+        vec![Block::BlockQuote(vec![Block::Paragraph(vec![text("Quote")])])],
+        parse("> Quote").blocks
+    );
+    assert_eq!(
+        vec![Block::List {
+            start: None,
+            tight: true,
+            items: vec![vec![Block::Paragraph(vec![text("a")])], vec![Block::Paragraph(vec![text("b")])]],
+        }],
+        parse("- a\n- b").blocks
+    );
+}
+
+#[test]
+fn inlines() {
+    assert_eq!(
+        vec![Block::Paragraph(vec![
+            Inline::Strong(vec![text("bold")]),
+            text(" and "),
+            Inline::Code("code".to_string()),
+        ])],
+        parse("**bold** and `code`").blocks
+    );
+    assert_eq!(
+        vec![Block::Paragraph(vec![
+            Inline::Html("<kbd>".to_string()),
+            text("Ctrl"),
+            Inline::Html("</kbd>".to_string()),
+        ])],
+        parse("<kbd>Ctrl</kbd>").blocks
+    );
+}
+
+#[test]
+fn non_ascii() {
+    assert_eq!(vec![Block::Paragraph(vec![text("héllo")])], parse("héllo").blocks);
+}
+
+#[test]
+fn transform() {
+    let mut document = Document::from_str("# Header");
+    for block in document.blocks.iter_mut() {
+        if let Block::Heading { ref mut level, .. } = *block {
+            *level += 1;
+        }
+    }
+    assert_eq!(
+        Document::new(vec![Block::Heading { level: 2, content: vec![text("Header")] }]),
+        document
+    );
+}
@@ -16,27 +16,29 @@ fn header_paragraph() {
     assert_eq!("<h1>Header</h1><h1>Header</h1>", convert("Header\n===\n\n# Header"));
     assert_eq!("<p>Content</p>", convert("Content"));
     assert_eq!("<h5>Header</h5><p>Content</p>", convert("##### Header\n\nContent"));
-    assert_eq!("<p>Content\n</p><h1>Header</h1>", convert("Content\n# Header"));
+    assert_eq!("<p>Content</p><h1>Header</h1>", convert("Content\n# Header"));
 }
-/*
 #[test]
 fn blockquote_paragraph() {
     assert_eq!("<blockquote><p>Quote</p></blockquote>", convert(">Quote"));
     assert_eq!("<p>foo</p><blockquote><p>bar</p></blockquote>", convert("foo\n>bar"))
 }
-*/
 #[test]
 fn emphasis() {
     assert_eq!("<p><strong>bold</strong></p>", convert("**bold**"));
     assert_eq!("<p><em>bold</em></p>", convert("*bold*"));
-    assert_eq!("<p><strong><em>bold</em></strong></p>", convert("***bold***"));
-    assert_eq!("<p>*<strong><em>bold</em></strong>*</p>", convert("****bold****"));
+    assert_eq!("<p><em><strong>bold</strong></em></p>", convert("***bold***"));
+    assert_eq!("<p><strong><strong>bold</strong></strong></p>", convert("****bold****"));
 
     // Probably will not happen, but you never know!
-    assert_eq!("<p>****<strong><em>bold</em></strong>****</p>", convert("*******bold*******"));
+    assert_eq!(
+        "<p><em><strong><strong><strong>bold</strong></strong></strong></em></p>",
+        convert("*******bold*******")
+    );
 
     // Unclosed
-    assert_eq!("<p>**bold*</p>", convert("**bold*"));
+    assert_eq!("<p>*<em>bold</em></p>", convert("**bold*"));
+    assert_eq!("<p>**bold</p>", convert("**bold"));
 }
 
 #[test]
@@ -56,3 +58,22 @@ fn code_block() {
 fn inline_code() {
     assert_eq!("<p>Some <code>*code*</code></p>", convert("Some `*code*`"));
 }
+
+#[test]
+fn inline_link() {
+    assert_eq!("<p>A <a href=\"http://example.com\">link</a>.</p>", convert("A [link](http://example.com)."));
+    assert_eq!("<p><a href=\"a%20b\">c</a></p>", convert("[c](<a b>)"));
+}
+
+#[test]
+fn escaping() {
+    assert_eq!("<p>a &lt; b &amp;&amp; c &gt; &quot;d&quot;</p>", convert("a < b && c > \"d\""));
+    assert_eq!("<p><code>&lt;br&gt;</code></p>", convert("`<br>`"));
+    assert_eq!("<pre><code>&lt;script&gt;alert(1)&lt;/script&gt;</code></pre>", convert("    <script>alert(1)</script>"));
+    assert_eq!(
+        "<p><a href=\"/a?b=1&amp;c=%22\" title=\"&lt;&quot;&gt;\">d</a></p>",
+        convert("[d](/a?b=1&c=\" '<\">')")
+    );
+    assert_eq!("<p><a href=\"/url%20with%5Bbrackets%5D%25\">x</a></p>", convert("[x](</url with[brackets]%>)"));
+    assert_eq!("<p><a href=\"/already%20encoded\">x</a></p>", convert("[x](/already%20encoded)"));
+}
@@ -0,0 +1,169 @@
+extern crate johnmark;
+
+use johnmark::{convert_with_options, Options, SoftBreak};
+
+#[test]
+fn defaults() {
+    let input = "# Header\n\n***";
+    assert_eq!(johnmark::convert(input), convert_with_options(input, &Options::new()));
+}
+
+#[test]
+fn void_tags() {
+    assert_eq!("<hr />", convert_with_options("***", &Options::new()));
+    assert_eq!("<hr>", convert_with_options("***", &Options::new().xhtml(false)));
+}
+
+#[test]
+fn pretty() {
+    let options = Options::new().pretty(true);
+    assert_eq!("<h1>Header</h1>\n<p>Content</p>\n", convert_with_options("# Header\n\nContent", &options));
+    assert_eq!("<blockquote>\n<p>Quote</p>\n</blockquote>\n", convert_with_options("> Quote", &options));
+}
+
+#[test]
+fn safe() {
+    let options = Options::new().safe(true);
+    assert_eq!("<p>&lt;b&gt;bold&lt;/b&gt;</p>", convert_with_options("<b>bold</b>", &options));
+    assert_eq!("<p><a href=\"a%22b\">c</a></p>", convert_with_options("[c](a\"b)", &options));
+
+    // Raw HTML is escaped, both as a block and inline.
+    assert_eq!(
+        "<p>&lt;div onclick=&quot;x()&quot;&gt;\nhi\n&lt;/div&gt;</p>",
+        convert_with_options("<div onclick=\"x()\">\nhi\n</div>", &options)
+    );
+    assert_eq!(
+        "<p>a &lt;img src=x onerror=y&gt; &lt;!-- b --&gt;</p>",
+        convert_with_options("a <img src=x onerror=y> <!-- b -->", &options)
+    );
+    assert_eq!("<p>a <!-- b --></p>", convert_with_options("a <!-- b -->", &Options::new()));
+
+    // Dangerous URLs become empty.
+    assert_eq!("<p><a href=\"\">x</a></p>", convert_with_options("[x](javascript:alert(1))", &options));
+    assert_eq!("<p><a href=\"\">x</a></p>", convert_with_options("[x](JavaScript&#58;alert(1))", &options));
+    assert_eq!("<p><a href=\"\">x</a></p>", convert_with_options("[x](java&#9;script:alert(1))", &options));
+    assert_eq!("<p><a href=\"\">x</a></p>", convert_with_options("[x](vbscript:msgbox)", &options));
+    assert_eq!("<p><a href=\"\">x</a></p>", convert_with_options("[x](file:///etc/passwd)", &options));
+    assert_eq!(
+        "<p><a href=\"\">javascript:alert(1)</a></p>",
+        convert_with_options("<javascript:alert(1)>", &options)
+    );
+    assert_eq!(
+        "<p><img src=\"\" alt=\"x\" /></p>",
+        convert_with_options("![x](data:text/html;base64,PHNjcmlwdD4=)", &options)
+    );
+    assert_eq!(
+        "<p><img src=\"data:image/png;base64,iVBO\" alt=\"x\" /></p>",
+        convert_with_options("![x](data:image/png;base64,iVBO)", &options)
+    );
+    assert_eq!(
+        "<p><a href=\"javascript:alert(1)\">x</a></p>",
+        convert_with_options("[x](javascript:alert(1))", &Options::new())
+    );
+}
+
+#[test]
+fn tab_width() {
+    assert_eq!("<pre><code>foo</code></pre>", convert_with_options("\tfoo", &Options::new()));
+    assert_eq!("<p>foo</p>", convert_with_options("\tfoo", &Options::new().tab_width(2)));
+
+    // Tabs after the indentation are kept.
+    assert_eq!("<pre><code>a\tb</code></pre>", convert_with_options("\t\ta\tb", &Options::new().tab_width(2)));
+}
+
+#[test]
+fn soft_break() {
+    let input = "foo \n bar  \nbaz";
+    assert_eq!("<p>foo\nbar<br />\nbaz</p>", convert_with_options(input, &Options::new()));
+    let options = Options::new().soft_break(SoftBreak::Space);
+    assert_eq!("<p>foo bar<br />\nbaz</p>", convert_with_options(input, &options));
+    let options = Options::new().soft_break(SoftBreak::Break).xhtml(false);
+    assert_eq!("<p>foo<br>\nbar<br>\nbaz</p>", convert_with_options(input, &options));
+}
+
+#[test]
+fn html5_image() {
+    let options = Options::new().xhtml(false);
+    assert_eq!("<p><img src=\"a.png\" alt=\"a\"></p>", convert_with_options("![a](a.png)", &options));
+}
+
+fn csv(code: &str, info: &str) -> String {
+    let cells: Vec<String> = code.trim().split(',').map(|cell| format!("<td>{}</td>", cell)).collect();
+    format!("<table data-info=\"{}\"><tr>{}</tr></table>", info, cells.concat())
+}
+
+fn dot(_code: &str, _info: &str) -> String {
+    "<svg></svg>".to_string()
+}
+
+#[test]
+fn code_block_handlers() {
+    let options = Options::new().code_block_handler("csv", csv).code_block_handler("dot", dot);
+    assert_eq!(
+        "<table data-info=\"csv header\"><tr><td>a</td><td>b</td></tr></table>",
+        convert_with_options("```csv header\na,b\n```", &options)
+    );
+    assert_eq!("<svg></svg><p>after</p>", convert_with_options("~~~dot\ndigraph {}\n~~~\nafter", &options));
+
+    // Other code is rendered as usual.
+    assert_eq!(
+        "<pre><code class=\"language-rust\">fn main() {}\n</code></pre>",
+        convert_with_options("```rust\nfn main() {}\n```", &options)
+    );
+    assert_eq!("<pre><code>a,b</code></pre>", convert_with_options("    a,b", &options));
+
+    // A later handler replaces an earlier one.
+    let options = options.code_block_handler("csv", dot);
+    assert_eq!("<svg></svg>", convert_with_options("```csv\na,b\n```", &options));
+
+    // Handlers can capture configuration.
+    let theme = "dark".to_string();
+    let options = Options::new().code_block_handler("dot", move |_code, _info| format!("<svg class=\"{}\"></svg>", theme));
+    assert_eq!("<svg class=\"dark\"></svg>", convert_with_options("```dot\ndigraph {}\n```", &options));
+    assert_eq!(options, options.clone());
+    assert!(options != Options::new().code_block_handler("dot", dot));
+    assert!(format!("{:?}", options).contains("code_block_handlers: [\"dot\"]"));
+
+    // Handlers are given the code with its tabs.
+    let options = Options::new().code_block_handler("tsv", |code, _info| code.replace('\t', ","));
+    assert_eq!("a,b\n", convert_with_options("```tsv\na\tb\n```", &options));
+}
+
+#[test]
+fn highlight() {
+    let input = "```rust\nfn f<'a>(s: &'a str) -> u8 { b'<' } // \"quoted\"\n```";
+    assert_eq!(
+        "<pre><code class=\"language-rust\">fn f&lt;'a&gt;(s: &amp;'a str) -&gt; u8 { b'&lt;' } // &quot;quoted&quot;\n</code></pre>",
+        convert_with_options(input, &Options::new())
+    );
+    let options = Options::new().highlight(true);
+    assert_eq!(
+        "<pre><code class=\"language-rust\"><span class=\"keyword\">fn</span> f&lt;<span class=\"lifetime\">'a</span>&gt;\
+         (s: &amp;<span class=\"lifetime\">'a</span> str) -&gt; u8 { <span class=\"string\">b'&lt;'</span> } \
+         <span class=\"comment\">// &quot;quoted&quot;</span>\n</code></pre>",
+        convert_with_options(input, &options)
+    );
+    assert_eq!(
+        "<pre><code class=\"language-toml\">[package]\nname = <span class=\"string\">&quot;johnmark&quot;</span> \
+         <span class=\"comment\"># zero dependencies</span>\n</code></pre>",
+        convert_with_options("```toml\n[package]\nname = \"johnmark\" # zero dependencies\n```", &options)
+    );
+    assert_eq!(
+        "<pre><code class=\"language-sh\"><span class=\"keyword\">if</span> true; <span class=\"keyword\">then</span> \
+         echo a#b; <span class=\"keyword\">fi</span>\n</code></pre>",
+        convert_with_options("```sh\nif true; then echo a#b; fi\n```", &options)
+    );
+    assert_eq!(
+        "<pre><code class=\"language-json\">{<span class=\"string\">&quot;a&quot;</span>: \
+         [<span class=\"number\">1.5</span>, <span class=\"keyword\">null</span>]}\n</code></pre>",
+        convert_with_options("```json\n{\"a\": [1.5, null]}\n```", &options)
+    );
+
+    // Unknown languages and handlers are left alone.
+    assert_eq!(
+        "<pre><code class=\"language-python\">if x: pass\n</code></pre>",
+        convert_with_options("```python\nif x: pass\n```", &options)
+    );
+    let options = options.code_block_handler("rust", dot);
+    assert_eq!("<svg></svg>", convert_with_options("```rust\nfn main() {}\n```", &options));
+}
@@ -0,0 +1,89 @@
+extern crate johnmark;
+
+use johnmark::{Event, Parser, Tag};
+
+fn text(string: &str) -> Event {
+    Event::Text(string.to_string())
+}
+
+#[test]
+fn paragraphs() {
+    let events: Vec<Event> = Parser::new("Some *text*\nhere").collect();
+    assert_eq!(
+        vec![
+            Event::Start(Tag::Paragraph),
+            text("Some "),
+            Event::Start(Tag::Emphasis),
+            text("text"),
+            Event::End(Tag::Emphasis),
+            Event::SoftBreak,
+            text("here"),
+            Event::End(Tag::Paragraph),
+        ],
+        events
+    );
+}
+
+#[test]
+fn blocks() {
+    let events: Vec<Event> = Parser::new("> Quote").collect();
+    assert_eq!(
+        vec![
+            Event::Start(Tag::BlockQuote),
+            Event::Start(Tag::Paragraph),
+            text("Quote"),
+            Event::End(Tag::Paragraph),
+            Event::End(Tag::BlockQuote),
+        ],
+        events
+    );
+    let events: Vec<Event> = Parser::new("    code").collect();
+    let code = Tag::CodeBlock(String::new());
+    assert_eq!(vec![Event::Start(code.clone()), text("code"), Event::End(code)], events);
+    assert_eq!(vec![Event::Rule], Parser::new("***").collect::<Vec<Event>>());
+    let events: Vec<Event> = Parser::new("<div>\n*hi*\n</div>").collect();
+    assert_eq!(vec![Event::Html("<div>\n*hi*\n</div>".to_string())], events);
+}
+
+#[test]
+fn lists() {
+    let events: Vec<Event> = Parser::new("- a\n- b").collect();
+    assert_eq!(
+        vec![
+            Event::Start(Tag::List(None)),
+            Event::Start(Tag::Item),
+            text("a"),
+            Event::End(Tag::Item),
+            Event::Start(Tag::Item),
+            text("b"),
+            Event::End(Tag::Item),
+            Event::End(Tag::List(None)),
+        ],
+        events
+    );
+    let events: Vec<Event> = Parser::new("- a\n\n- b").collect();
+    assert_eq!(Some(&Event::Start(Tag::Paragraph)), events.get(2));
+}
+
+#[test]
+fn filter() {
+    let code: Vec<String> = Parser::new("`a` and `b`")
+        .filter_map(|event| match event {
+            Event::Code(code) => Some(code),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(vec!["a".to_string(), "b".to_string()], code);
+}
+
+#[test]
+fn deep_nesting() {
+    let stars = "*".repeat(50000);
+    let input = format!("{}a{}", stars, stars);
+    let strong = Parser::new(&input).filter(|event| *event == Event::Start(Tag::Strong)).count();
+    assert_eq!(100, strong);
+
+    let input = format!("{}a{}", "![".repeat(50000), "](u)".repeat(50000));
+    let images = Parser::new(&input).filter(|event| matches!(*event, Event::Start(Tag::Image(..)))).count();
+    assert_eq!(100, images);
+}
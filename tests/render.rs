@@ -0,0 +1,48 @@
+extern crate johnmark;
+
+use johnmark::{Document, HtmlRenderer, Inline, Renderer};
+
+/// Counts the headings it sees and gives each one an id.
+struct Numbered {
+    count: usize,
+}
+
+impl Renderer for Numbered {
+    fn heading(&mut self, output: &mut String, level: usize, content: &[Inline]) {
+        self.count += 1;
+        output.push_str(&format!("<h{} id=\"section-{}\">", level, self.count));
+        self.inlines(output, content);
+        output.push_str(&format!("</h{}>", level));
+    }
+}
+
+/// Renders emphasis without any markup.
+struct Plain;
+
+impl Renderer for Plain {
+    fn emphasis(&mut self, output: &mut String, content: &[Inline]) {
+        self.inlines(output, content);
+    }
+}
+
+#[test]
+fn default_renderer() {
+    let input = "# Header\n\nSome *text*";
+    assert_eq!(johnmark::convert(input), HtmlRenderer::new().render(&Document::from_str(input)));
+}
+
+#[test]
+fn override_heading() {
+    let document = Document::from_str("# One\n\n## Two");
+    let mut renderer = Numbered { count: 0 };
+    assert_eq!(
+        "<h1 id=\"section-1\">One</h1><h2 id=\"section-2\">Two</h2>",
+        renderer.render(&document)
+    );
+}
+
+#[test]
+fn override_inline() {
+    let document = Document::from_str("Some *text* and **more**");
+    assert_eq!("<p>Some text and <strong>more</strong></p>", Plain.render(&document));
+}